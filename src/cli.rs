@@ -0,0 +1,317 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::io::{BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use crate::rsb_lib;
+#[cfg(feature = "repl")]
+use crate::repl;
+use crate::utils::{ast::BoolNode, truth, sets};
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// At least one input could not be parsed or evaluated.
+pub const EXIT_FAILURE: i32 = 1;
+/// The command line itself is invalid.
+pub const EXIT_USAGE: i32 = 2;
+
+pub const USAGE: &str = "\
+usage: ready_set_boole [-f rpn|infix|tree] [-s SET]... COMMAND [INPUT]...
+
+Every INPUT is handled on its own. When none is given, they are read from the
+standard input, one per line (empty lines are skipped).
+
+commands:
+    eval       evaluate a formula of constants ('10&')
+    table      print the truth table of a formula
+    nnf        negation normal form of a formula
+    cnf        conjunctive normal form of a formula
+    dnf        disjunctive normal form of a formula
+    sat        check if a formula is satisfiable
+    tree       print the tree of a formula
+    set        evaluate a set formula on the sets given with -s
+    powerset   print every subset of a set ('1,2,3')
+    map        map a pair of coordinates ('x,y') to a number in [0;1]
+    unmap      reverse of map
+    gray       gray code of a number
+    repl       start an interactive session (takes no input)
+
+options:
+    -f, --format FORMAT  output format of nnf, cnf and dnf (default: rpn)
+    -s, --set SET        comma separated set for the next variable of 'set'
+    -h, --help           print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Rpn,
+	Infix,
+	Tree,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+	Eval,
+	Table,
+	Nnf,
+	Cnf,
+	Dnf,
+	Sat,
+	Tree,
+	Set,
+	Powerset,
+	Map,
+	Unmap,
+	Gray,
+	Repl,
+}
+
+impl Command {
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"eval" => Some(Command::Eval),
+			"table" => Some(Command::Table),
+			"nnf" => Some(Command::Nnf),
+			"cnf" => Some(Command::Cnf),
+			"dnf" => Some(Command::Dnf),
+			"sat" => Some(Command::Sat),
+			"tree" => Some(Command::Tree),
+			"set" => Some(Command::Set),
+			"powerset" => Some(Command::Powerset),
+			"map" => Some(Command::Map),
+			"unmap" => Some(Command::Unmap),
+			"gray" => Some(Command::Gray),
+			"repl" => Some(Command::Repl),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+	pub command: Command,
+	pub format: Format,
+	pub sets: Vec<Vec<i32>>,
+	pub inputs: Vec<String>,
+}
+
+fn parse_set(input: &str) -> Result<Vec<i32>, String> {
+	let input = input.trim();
+	let input = input.strip_prefix('{').unwrap_or(input);
+	let input = input.strip_suffix('}').unwrap_or(input);
+	input.split(',')
+		.map(str::trim)
+		.filter(|element| !element.is_empty())
+		.map(|element| element.parse::<i32>()
+			.map_err(|_| format!("'{}' is not a valid set element", element)))
+		.collect()
+}
+
+fn parse_number<T: std::str::FromStr>(input: &str) -> Result<T, String> {
+	input.trim().parse::<T>()
+		.map_err(|_| format!("'{}' is not a valid number", input.trim()))
+}
+
+fn set_string(set: &[i32]) -> String {
+	let elements: Vec<String> = set.iter().map(|e| e.to_string()).collect();
+	format!("{{{}}}", elements.join(", "))
+}
+
+/// Parse the command line (without the program name). Returns `Ok(None)`
+/// when help is requested.
+pub fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+	let mut format = Format::Rpn;
+	let mut sets: Vec<Vec<i32>> = vec![];
+	let mut command: Option<Command> = None;
+	let mut inputs: Vec<String> = vec![];
+	let mut args = args.iter();
+
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-h" | "--help" => return Ok(None),
+			"-f" | "--format" => {
+				format = match args.next().map(String::as_str) {
+					Some("rpn") => Format::Rpn,
+					Some("infix") => Format::Infix,
+					Some("tree") => Format::Tree,
+					Some(other) => {
+						return Err(format!("'{}' is not a valid format", other))
+					},
+					None => return Err(format!("missing value for '{}'", arg)),
+				};
+			},
+			"-s" | "--set" => match args.next() {
+				Some(set) => sets.push(parse_set(set)?),
+				None => return Err(format!("missing value for '{}'", arg)),
+			},
+			"--" => {
+				inputs.extend(args.by_ref().cloned());
+			},
+			_ if command.is_none() => match Command::from_name(arg) {
+				Some(name) => command = Some(name),
+				None => return Err(format!("'{}' is not a valid command", arg)),
+			},
+			_ => inputs.push(arg.clone()),
+		}
+	}
+	match command {
+		Some(command) => Ok(Some(Options { command, format, sets, inputs })),
+		None => Err(String::from("missing command")),
+	}
+}
+
+fn formula_string(tree: &BoolNode, format: Format) -> String {
+	match format {
+		Format::Rpn => tree.to_formula(),
+		Format::Infix => tree.to_infix(),
+		Format::Tree => tree.to_string(),
+	}
+}
+
+/// Output of a command for one input. Errors in the library are panics, so
+/// this may panic on invalid input.
+fn process(options: &Options, input: &str) -> Result<String, String> {
+	let output = match options.command {
+		Command::Eval => truth::eval_formula(input).to_string(),
+		Command::Table => {
+			let table = truth::table::<Vec<u8>>(input, None)
+				.ok_or_else(|| String::from("no truth table was built"))?;
+			table.trim_end().to_string()
+		},
+		Command::Nnf | Command::Cnf | Command::Dnf => {
+			let mut tree = BoolNode::tree(input);
+			match options.command {
+				Command::Nnf => tree.to_nnf(),
+				Command::Cnf => tree.to_cnf(),
+				_ => tree.to_dnf(),
+			}
+			formula_string(&tree, options.format)
+		},
+		Command::Sat => truth::sat(input).to_string(),
+		Command::Tree => BoolNode::tree(input).to_string(),
+		Command::Set => set_string(&sets::eval_set(input, &options.sets)),
+		Command::Powerset => {
+			let powerset = sets::powerset(&parse_set(input)?);
+			let subsets: Vec<String> = powerset.iter()
+				.map(|subset| set_string(subset))
+				.collect();
+			subsets.join("\n")
+		},
+		Command::Map => match input.split_once(',') {
+			Some((x, y)) => {
+				rsb_lib::map(parse_number(x)?, parse_number(y)?).to_string()
+			},
+			None => return Err(format!("'{}' is not a pair 'x,y'", input)),
+		},
+		Command::Unmap => {
+			let (x, y) = rsb_lib::reverse_map(parse_number(input)?);
+			format!("{},{}", x, y)
+		},
+		Command::Gray => rsb_lib::gray_code(parse_number(input)?).to_string(),
+		Command::Repl => unreachable!("the repl does not process inputs"),
+	};
+	Ok(output)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+	if let Some(message) = payload.downcast_ref::<String>() {
+		message.clone()
+	} else if let Some(message) = payload.downcast_ref::<&str>() {
+		message.to_string()
+	} else {
+		String::from("unknown error")
+	}
+}
+
+thread_local! {
+	/// Whether the current thread is in a call to `catch_panic`.
+	static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Make the panic hook silent on the threads which are in a call to
+/// `catch_panic`. The previous hook keeps reporting the panics of every
+/// other thread (and of this one outside of `catch_panic`). The hook is only
+/// installed the first time this is called.
+pub(crate) fn install_panic_hook() {
+	static INSTALL: Once = Once::new();
+
+	INSTALL.call_once(|| {
+		let hook = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			if !CATCHING.with(Cell::get) {
+				hook(info);
+			}
+		}));
+	});
+}
+
+/// Run `f`, returning the message of its panic as an error. The library
+/// reports invalid formulas by panicking, which the hook of
+/// `install_panic_hook` does not print while `f` runs.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+	let catching = CATCHING.with(|catching| catching.replace(true));
+	let result = panic::catch_unwind(AssertUnwindSafe(f));
+	CATCHING.with(|flag| flag.set(catching));
+	result.unwrap_or_else(|payload| Err(panic_message(payload)))
+}
+
+/// Run the command line `args` (without the program name), reading inputs
+/// from `input` if there are none in `args`. Returns the exit code.
+pub fn run<R: BufRead, W: Write>(args: &[String], input: R, output: &mut W) -> i32 {
+	install_panic_hook();
+	let options = match parse_args(args) {
+		Ok(Some(options)) => options,
+		Ok(None) => {
+			return match output.write_all(USAGE.as_bytes()) {
+				Ok(_) => EXIT_SUCCESS,
+				Err(_) => EXIT_FAILURE,
+			}
+		},
+		Err(message) => {
+			eprintln!("ready_set_boole: {}\n\n{}", message, USAGE);
+			return EXIT_USAGE;
+		},
+	};
+	if options.command == Command::Repl {
+		if !options.inputs.is_empty() {
+			eprintln!("ready_set_boole: 'repl' takes no input\n\n{}", USAGE);
+			return EXIT_USAGE;
+		}
+		#[cfg(feature = "repl")]
+		return repl::run();
+		#[cfg(not(feature = "repl"))]
+		{
+			eprintln!("ready_set_boole: built without the 'repl' feature");
+			return EXIT_USAGE;
+		}
+	}
+	let inputs: Box<dyn Iterator<Item = Result<String, std::io::Error>>> =
+		if options.inputs.is_empty() {
+			Box::new(input.lines())
+		} else {
+			Box::new(options.inputs.clone().into_iter().map(Ok))
+		};
+	let mut code = EXIT_SUCCESS;
+	for line in inputs {
+		let line = match line {
+			Ok(line) => line,
+			Err(error) => {
+				eprintln!("ready_set_boole: {}", error);
+				return EXIT_FAILURE;
+			},
+		};
+		let line = line.trim();
+		if line.is_empty() { continue };
+		match catch_panic(|| process(&options, line)) {
+			Ok(result) => {
+				if writeln!(output, "{}", result).is_err() {
+					return EXIT_FAILURE;
+				}
+			},
+			Err(message) => {
+				eprintln!("ready_set_boole: '{}': {}", line, message);
+				code = EXIT_FAILURE;
+			},
+		}
+	}
+	code
+}
@@ -0,0 +1,8 @@
+pub mod rsb_lib;
+pub mod utils;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "repl")]
+pub mod repl;
+
+pub use rsb_lib::*;
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use rustyline::{Editor, error::ReadlineError};
+use crate::cli::{self, catch_panic};
+use crate::utils::{ast::{BoolNode, BoolType}, truth};
+
+/// Where the line history is kept between sessions (current directory).
+pub const HISTORY_FILE: &str = ".rsb_history";
+
+pub const HELP: &str = "\
+commands:
+    let NAME = FORMULA      define a named formula (NAME is in lowercase)
+    show FORMULA            print the tree, RPN and infix forms
+    step NAME RULE [PATH]   apply a rule at PATH (default: root, 'all': every node)
+    undo NAME               revert the last change of a named formula
+    eq FORMULA FORMULA      check if two formulas are equivalent
+    table FORMULA           print a truth table
+    list                    print every named formula
+    help                    print this help
+    quit                    leave (also Ctrl-D)
+
+FORMULA is a name or an RPN formula, PATH is a sequence of 'l' (left) and 'r'
+(right) steps from the root. A line holding only a FORMULA shows it.
+
+rules:
+    material, equivalence, xor, double-negation, de-morgan,
+    distribute-and, distribute-or, factor-and, factor-or,
+    rotate-left-and, rotate-left-or, rotate-right-and, rotate-right-or,
+    nnf, cnf, dnf
+";
+
+fn is_name(word: &str) -> bool {
+	let mut chars = word.chars();
+	matches!(chars.next(), Some('a'..='z'))
+		&& chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn rule(name: &str) -> Result<fn(&mut BoolNode), String> {
+	let rule: fn(&mut BoolNode) = match name {
+		"material" => BoolNode::replace_material_condition,
+		"equivalence" => BoolNode::replace_logical_equivalence,
+		"xor" => BoolNode::replace_exclusive_disjunction,
+		"double-negation" => BoolNode::eliminate_double_negation,
+		"de-morgan" => BoolNode::replace_junction_negation,
+		"distribute-and" => |node| node.distribute(BoolType::Conjunction),
+		"distribute-or" => |node| node.distribute(BoolType::Disjunction),
+		"factor-and" => |node| node.factor(BoolType::Conjunction),
+		"factor-or" => |node| node.factor(BoolType::Disjunction),
+		"rotate-left-and" => |node| node.left_rotate(BoolType::Conjunction),
+		"rotate-left-or" => |node| node.left_rotate(BoolType::Disjunction),
+		"rotate-right-and" => |node| node.right_rotate(BoolType::Conjunction),
+		"rotate-right-or" => |node| node.right_rotate(BoolType::Disjunction),
+		"nnf" => BoolNode::to_nnf,
+		"cnf" => BoolNode::to_cnf,
+		"dnf" => BoolNode::to_dnf,
+		_ => return Err(format!("'{}' is not a valid rule", name)),
+	};
+	Ok(rule)
+}
+
+fn show(tree: &BoolNode) -> String {
+	format!("{}\nrpn: {}\ninfix: {}", tree, tree.to_formula(), tree.to_infix())
+}
+
+/// State of an interactive session: every named formula with the versions
+/// it went through, the current one being last.
+#[derive(Debug, Default)]
+pub struct Session {
+	formulas: HashMap<String, Vec<BoolNode>>,
+}
+
+impl Session {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Current version of a named formula.
+	pub fn get(&self, name: &str) -> Option<&BoolNode> {
+		self.formulas.get(name).and_then(|versions| versions.last())
+	}
+
+	fn resolve(&self, word: &str) -> Result<BoolNode, String> {
+		if is_name(word) {
+			match self.get(word) {
+				Some(tree) => Ok(tree.clone()),
+				None => Err(format!("'{}' is not defined", word)),
+			}
+		} else {
+			Ok(BoolNode::tree(word))
+		}
+	}
+
+	fn define(&mut self, name: &str, tree: BoolNode) {
+		self.formulas.entry(name.to_string()).or_default().push(tree);
+	}
+
+	fn step(&mut self, name: &str, rule_name: &str, path: &str) -> Result<String, String> {
+		if !is_name(name) {
+			return Err(format!("'{}' is not a valid name", name));
+		}
+		let rule = rule(rule_name)?;
+		let mut tree = self.resolve(name)?;
+		if path == "all" {
+			tree.pre_order(rule);
+		} else {
+			match tree.subtree_mut(path) {
+				Some(node) => rule(node),
+				None => return Err(format!("no node at path '{}'", path)),
+			}
+		}
+		if Some(tree.to_formula()) == self.get(name).map(BoolNode::to_formula) {
+			return Ok(format!("{}\n(unchanged)", show(&tree)));
+		}
+		let output = show(&tree);
+		self.define(name, tree);
+		Ok(output)
+	}
+
+	fn undo(&mut self, name: &str) -> Result<String, String> {
+		match self.formulas.get_mut(name) {
+			Some(versions) if versions.len() > 1 => {
+				versions.pop();
+				Ok(show(versions.last().unwrap()))
+			},
+			Some(_) => Err(format!("nothing to undo for '{}'", name)),
+			None => Err(format!("'{}' is not defined", name)),
+		}
+	}
+
+	fn list(&self) -> String {
+		let mut names: Vec<&String> = self.formulas.keys().collect();
+		names.sort();
+		let lines: Vec<String> = names.iter()
+			.map(|name| format!("{} = {}", name, self.get(name).unwrap().to_formula()))
+			.collect();
+		lines.join("\n")
+	}
+
+	fn command(&mut self, line: &str) -> Result<String, String> {
+		let words: Vec<&str> = line.split_whitespace().collect();
+		match words.as_slice() {
+			["let", name, "=", formula] | [name, "=", formula] => {
+				if !is_name(name) {
+					return Err(format!("'{}' is not a valid name", name));
+				}
+				let tree = self.resolve(formula)?;
+				let output = show(&tree);
+				self.define(name, tree);
+				Ok(output)
+			},
+			["step", name, rule] => self.step(name, rule, ""),
+			["step", name, rule, path] => self.step(name, rule, path),
+			["undo", name] => self.undo(name),
+			["eq", left, right] => {
+				if self.resolve(left)? == self.resolve(right)? {
+					Ok(String::from("equivalent"))
+				} else {
+					Ok(String::from("not equivalent"))
+				}
+			},
+			["table", formula] => {
+				let formula = self.resolve(formula)?.to_formula();
+				let table = truth::table::<Vec<u8>>(&formula, None).unwrap_or_default();
+				Ok(table.trim_end().to_string())
+			},
+			["list"] => Ok(self.list()),
+			["help"] => Ok(HELP.trim_end().to_string()),
+			["show", formula] | [formula] => Ok(show(&self.resolve(formula)?)),
+			_ => Err(format!("invalid command '{}' (try 'help')", line)),
+		}
+	}
+
+	/// Run one line of input. The library reports errors by panicking, these
+	/// are caught and returned as errors too (without being printed, the hook
+	/// of `cli::install_panic_hook` being installed the first time).
+	pub fn execute(&mut self, line: &str) -> Result<String, String> {
+		cli::install_panic_hook();
+		catch_panic(|| self.command(line))
+	}
+}
+
+/// Interactive loop on the terminal. Returns the exit code.
+pub fn run() -> i32 {
+	let mut editor = Editor::<()>::new();
+	let mut session = Session::new();
+	let _ = editor.load_history(HISTORY_FILE);
+	loop {
+		match editor.readline("rsb> ") {
+			Ok(line) => {
+				let line = line.trim();
+				if line.is_empty() { continue };
+				editor.add_history_entry(line);
+				if line == "quit" || line == "exit" { break };
+				match session.execute(line) {
+					Ok(output) => println!("{}", output),
+					Err(message) => eprintln!("error: {}", message),
+				}
+			},
+			Err(ReadlineError::Interrupted) => continue,
+			Err(ReadlineError::Eof) => break,
+			Err(error) => {
+				eprintln!("error: {}", error);
+				return cli::EXIT_FAILURE;
+			},
+		}
+	}
+	if let Err(error) = editor.save_history(HISTORY_FILE) {
+		eprintln!("error: could not save history: {}", error);
+	}
+	cli::EXIT_SUCCESS
+}
@@ -1,3 +1,13 @@
 pub mod ast;
+pub mod bitset;
+#[cfg(feature = "solver")]
+pub mod encode;
+pub mod fuzzy;
+pub mod interval;
+#[cfg(feature = "solver")]
+pub mod maxsat;
+#[cfg(feature = "solver")]
+pub mod sat;
 pub mod sets;
 pub mod truth;
+pub mod venn;
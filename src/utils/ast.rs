@@ -1,8 +1,19 @@
 use std::fmt;
-use super::truth;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+use std::iter::Peekable;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Variables which are not meant to be written by hand but introduced by
+/// encodings (the Unicode private use area, so they never clash with 'A'-'Z').
+pub const AUXILIARY_VARIABLES: RangeInclusive<char> = '\u{E000}'..='\u{F8FF}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum BoolType {
+	True,
+	False,
 	Variable,
 	Negation,
 	Conjunction,
@@ -10,132 +21,1416 @@ pub enum BoolType {
 	ExclusiveDisjunction,
 	MaterialCondition,
 	LogicalEquivalence,
+	Existential,
+	Universal,
+	AtMost,
+	AtLeast,
+	Exactly,
 }
 
 impl fmt::Display for BoolType {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "{:?}", self)
 	}
-}
+}
+
+#[derive(Debug)]
+pub struct BoolNode {
+	boolean_type: BoolType,
+	op_symbol: char,
+	left: Option<Box<Self>>,
+	right: Option<Box<Self>>,
+	/// Operands of the cardinality constraints and of the n-ary
+	/// conjunctions and disjunctions (which have no left or right operand),
+	/// out of line so that the binary nodes stay small.
+	operands: Option<Box<Operands>>,
+}
+
+/// Operand list of an n-ary node, and the number of operands a cardinality
+/// constraint counts.
+#[derive(Debug, Clone)]
+struct Operands {
+	nodes: Vec<BoolNode>,
+	bound: usize,
+}
+
+impl fmt::Display for BoolNode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.tree_string())
+	}
+}
+
+impl Clone for BoolNode {
+	fn clone(&self) -> Self {
+		let mut ast = Self::new(self.op_symbol);
+		if let Some(left_node) = &self.left {
+			ast.left = Some(Box::new(*left_node.clone()));
+		}
+		if let Some(right_node) = &self.right {
+			ast.right = Some(Box::new(*right_node.clone()));
+		}
+		ast.operands = self.operands.clone();
+		ast
+	}
+}
+
+impl PartialEq for BoolNode {
+	/// This is not the smartest way to do this, but it was already done lol
+	fn eq(&self, other: &Self) -> bool {
+		let left_formula = self.to_formula();
+		let right_formula = other.to_formula();
+		if left_formula == right_formula { return true };
+		if Canonical::new(self) == Canonical::new(other) { return true };
+		let mut variables = self.variables();
+		variables.append(&mut other.variables());
+		variables.sort_unstable();
+		variables.dedup();
+		if variables.len() > TRUTH_TABLE_VARIABLES {
+			return equivalent(self, other, &variables);
+		}
+		same_truth_table(self, other, &variables)
+	}
+}
+
+/// Number of variables up to which formulas are compared on their truth
+/// tables. Past it the SAT solver decides whether they are equivalent.
+const TRUTH_TABLE_VARIABLES: usize = 16;
+
+fn same_truth_table(a: &BoolNode, b: &BoolNode, variables: &[char]) -> bool {
+	(0..1u64 << variables.len()).all(|values| {
+		let varmap: HashMap<char, bool> = variables.iter().enumerate()
+			.map(|(shift, var)| (*var, (values >> shift) & 1 != 0))
+			.collect();
+		a.eval(&varmap) == b.eval(&varmap)
+	})
+}
+
+/// Both formulas are equivalent if `!(a = b)` is unsatisfiable, which is
+/// solved on its Tseitin encoding.
+#[cfg(feature = "solver")]
+fn equivalent(a: &BoolNode, b: &BoolNode, _variables: &[char]) -> bool {
+	use super::encode::{tseitin, Fresh};
+	use super::sat::{Outcome, Solver};
+
+	let difference = BoolNode::unary('!', BoolNode::binary('=', a.clone(), b.clone()));
+	let mut solver = Solver::new();
+	for clause in tseitin(&difference, &mut Fresh::for_formula(&difference)) {
+		solver.add_clause(clause);
+	}
+	matches!(solver.solve(&[]), Outcome::Unsatisfiable(_))
+}
+
+/// Without the solver the truth tables are compared as long as they can be
+/// enumerated.
+#[cfg(not(feature = "solver"))]
+fn equivalent(a: &BoolNode, b: &BoolNode, variables: &[char]) -> bool {
+	if variables.len() >= 64 {
+		panic!("cannot compare formulas of {} variables without the 'solver' feature",
+			variables.len());
+	}
+	same_truth_table(a, b, variables)
+}
+
+impl Eq for BoolNode {}
+
+/// Formula in canonical form (see `BoolNode::canonicalize`), compared and
+/// hashed on its structure. Unlike `BoolNode`, which compares formulas by
+/// their truth tables, two equivalent formulas are only equal here if they
+/// differ by the order or grouping of commutative and associative operands.
+#[derive(Debug, Clone)]
+pub struct Canonical(BoolNode);
+
+impl Canonical {
+	pub fn new(formula: &BoolNode) -> Self {
+		let mut formula = formula.clone();
+		formula.canonicalize();
+		Canonical(formula)
+	}
+
+	pub fn formula(&self) -> &BoolNode {
+		&self.0
+	}
+
+	pub fn into_formula(self) -> BoolNode {
+		self.0
+	}
+}
+
+impl From<BoolNode> for Canonical {
+	fn from(mut formula: BoolNode) -> Self {
+		formula.canonicalize();
+		Canonical(formula)
+	}
+}
+
+impl PartialEq for Canonical {
+	fn eq(&self, other: &Self) -> bool {
+		self.0.structural_cmp(&other.0) == Ordering::Equal
+	}
+}
+
+impl Eq for Canonical {}
+
+impl PartialOrd for Canonical {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Canonical {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.0.structural_cmp(&other.0)
+	}
+}
+
+impl Hash for Canonical {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.0.hash_structure(state);
+	}
+}
+
+/// Values given to the variables of a formula. An assignment may be partial,
+/// in which case `value` returns `None` for the missing variables.
+pub trait Assignment {
+	fn value(&self, variable: char) -> Option<bool>;
+}
+
+/// Slices are indexed by letter: index 0 is 'A', index 1 is 'B' and so on.
+impl Assignment for [bool] {
+	fn value(&self, variable: char) -> Option<bool> {
+		match variable {
+			'A'..='Z' => self.get(variable as usize - 'A' as usize).copied(),
+			_ => None,
+		}
+	}
+}
+
+impl Assignment for Vec<bool> {
+	fn value(&self, variable: char) -> Option<bool> {
+		self.as_slice().value(variable)
+	}
+}
+
+impl Assignment for HashMap<char, bool> {
+	fn value(&self, variable: char) -> Option<bool> {
+		self.get(&variable).copied()
+	}
+}
+
+/// Bitmasks give the value of 'A' in bit 0, 'B' in bit 1 and so on.
+impl Assignment for u32 {
+	fn value(&self, variable: char) -> Option<bool> {
+		match variable {
+			'A'..='Z' => Some((self >> (variable as u32 - 'A' as u32)) & 1 != 0),
+			_ => None,
+		}
+	}
+}
+
+/// Assignment with bound variables on top of an other one.
+struct Overlay<'a, A: Assignment + ?Sized> {
+	base: &'a A,
+	bound: Vec<(char, bool)>,
+}
+
+impl<'a, A: Assignment + ?Sized> Assignment for Overlay<'a, A> {
+	fn value(&self, variable: char) -> Option<bool> {
+		match self.bound.iter().rev().find(|(var, _)| *var == variable) {
+			Some((_, value)) => Some(*value),
+			None => self.base.value(variable),
+		}
+	}
+}
+
+/// Tells a walk how to go on after a visitor callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+	/// Keep walking normally.
+	Continue,
+	/// Do not visit the children of the current node (only meaningful when
+	/// returned by `enter`).
+	SkipChildren,
+	/// Stop the walk right away.
+	Abort,
+}
+
+/// Read-only visitor. `enter` is called before the children of a node are
+/// visited and `leave` after them (so pre-order and post-order respectively).
+pub trait Visitor {
+	fn enter(&mut self, _node: &BoolNode) -> Flow { Flow::Continue }
+	fn leave(&mut self, _node: &BoolNode) -> Flow { Flow::Continue }
+}
+
+/// Same as `Visitor` but the nodes can be modified on the way. Children are
+/// visited after `enter` returns, so a node rewritten in `enter` will have
+/// its new children visited.
+pub trait VisitorMut {
+	fn enter(&mut self, _node: &mut BoolNode) -> Flow { Flow::Continue }
+	fn leave(&mut self, _node: &mut BoolNode) -> Flow { Flow::Continue }
+}
+
+struct FormulaWriter {
+	formula: String,
+}
+
+impl Visitor for FormulaWriter {
+	fn enter(&mut self, node: &BoolNode) -> Flow {
+		if node.is_cardinality() {
+			self.formula.push('{');
+		}
+		Flow::Continue
+	}
+
+	fn leave(&mut self, node: &BoolNode) -> Flow {
+		if node.is_cardinality() {
+			self.formula.push('}');
+			self.formula.push_str(&node.bound().to_string());
+		}
+		if node.is_nary() {
+			// written as the equivalent right-handed chain
+			for _ in 1..node.operands().len() {
+				self.formula.push(node.op_symbol);
+			}
+		} else {
+			self.formula.push(node.op_symbol);
+		}
+		Flow::Continue
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CnfShape {
+	Literal,
+	Clause,
+	Cnf,
+	Invalid,
+}
+
+/// One rewrite of a traced conversion: the rule which was applied, where
+/// (a path of 'l' and 'r' steps from the root, as for `subtree_mut`) and the
+/// subtree before and after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+	pub rule: &'static str,
+	pub path: String,
+	pub before: String,
+	pub after: String,
+}
+
+/// Every rewrite done by a conversion, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+	pub initial: String,
+	pub steps: Vec<Step>,
+}
+
+impl Trace {
+	/// The whole formula after each step, starting with the initial one.
+	pub fn formulas(&self) -> Vec<BoolNode> {
+		let mut formula = BoolNode::tree(&self.initial);
+		let mut formulas = vec![formula.clone()];
+		for step in &self.steps {
+			match formula.subtree_mut(&step.path) {
+				Some(node) => *node = BoolNode::tree(&step.after),
+				None => panic!("no node at path '{}'", step.path),
+			}
+			formulas.push(formula.clone());
+		}
+		formulas
+	}
+
+	/// The formulas of the conversion in infix notation, one per line, each
+	/// one with the rule leading to it.
+	pub fn derivation(&self) -> String {
+		let lines: Vec<String> = self.formulas().iter()
+			.map(|formula| formula.to_infix())
+			.collect();
+		let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+		let mut derivation = format!("   {}", lines[0]);
+		for (step, line) in self.steps.iter().zip(lines.iter().skip(1)) {
+			let path = if step.path.is_empty() { "root" } else { &step.path };
+			derivation.push_str(&format!("\n=  {}{}   [{} at {}]", line,
+				" ".repeat(width - line.chars().count()), step.rule, path));
+		}
+		derivation
+	}
+}
+
+impl fmt::Display for Trace {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.derivation())
+	}
+}
+
+/// Limits of a bounded CNF or DNF conversion (`None` for no limit). The
+/// clauses are the terms of a DNF.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+	pub max_nodes: Option<usize>,
+	pub max_clauses: Option<usize>,
+	pub max_time: Option<Duration>,
+}
+
+/// Why a bounded conversion was stopped, with the amount it reached (a lower
+/// bound of what the whole conversion would have needed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetExceeded {
+	Nodes(usize),
+	Clauses(usize),
+	Time(Duration),
+}
+
+impl fmt::Display for BudgetExceeded {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BudgetExceeded::Nodes(n) => write!(f, "conversion needs at least {} nodes", n),
+			BudgetExceeded::Clauses(n) => {
+				write!(f, "conversion needs at least {} clauses", n)
+			},
+			BudgetExceeded::Time(t) => write!(f, "conversion timed out after {:?}", t),
+		}
+	}
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Checks the budget along a conversion.
+struct Guard {
+	budget: Budget,
+	start: Instant,
+}
+
+impl Guard {
+	fn new(budget: Budget) -> Self {
+		Guard { budget, start: Instant::now() }
+	}
+
+	fn counts_size(&self) -> bool {
+		self.budget.max_nodes.is_some() || self.budget.max_clauses.is_some()
+	}
+
+	fn check(&self, nodes: usize, clauses: usize) -> Result<(), BudgetExceeded> {
+		match self.budget {
+			Budget { max_nodes: Some(max), .. } if nodes > max => {
+				Err(BudgetExceeded::Nodes(nodes))
+			},
+			Budget { max_clauses: Some(max), .. } if clauses > max => {
+				Err(BudgetExceeded::Clauses(clauses))
+			},
+			Budget { max_time: Some(max), .. } if self.start.elapsed() > max => {
+				Err(BudgetExceeded::Time(self.start.elapsed()))
+			},
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Records the rewrites of a conversion when it is traced.
+struct Tracer {
+	path: String,
+	steps: Option<Vec<Step>>,
+}
+
+impl Tracer {
+	fn new(enabled: bool) -> Self {
+		Tracer { path: String::new(), steps: if enabled { Some(vec![]) } else { None } }
+	}
+
+	fn apply(&mut self, rule: &'static str, node: &mut BoolNode,
+		op: impl FnOnce(&mut BoolNode)) {
+		match &mut self.steps {
+			None => op(node),
+			Some(steps) => {
+				let before = node.to_formula();
+				op(node);
+				let after = node.to_formula();
+				if before != after {
+					steps.push(Step { rule, path: self.path.clone(), before, after });
+				}
+			},
+		}
+	}
+}
+
+impl BoolNode {
+	fn symbol_to_type(c: char) -> BoolType {
+		match c {
+			'1' => BoolType::True,
+			'0' => BoolType::False,
+			'A' ..='Z' => BoolType::Variable,
+			'!' => BoolType::Negation,
+			'&' => BoolType::Conjunction,
+			'|' => BoolType::Disjunction,
+			'^' => BoolType::ExclusiveDisjunction,
+			'>' => BoolType::MaterialCondition,
+			'=' => BoolType::LogicalEquivalence,
+			'∃' => BoolType::Existential,
+			'∀' => BoolType::Universal,
+			'≤' => BoolType::AtMost,
+			'≥' => BoolType::AtLeast,
+			'#' => BoolType::Exactly,
+			_ if AUXILIARY_VARIABLES.contains(&c) => BoolType::Variable,
+			_ => panic!("'{}' is not a valid op", c),
+		}
+	}
+
+	fn type_to_symbol(boolean_type: BoolType) -> char {
+		match boolean_type {
+			BoolType::True => '1',
+			BoolType::False => '0',
+			BoolType::Negation => '!',
+			BoolType::Conjunction => '&',
+			BoolType::Disjunction => '|',
+			BoolType::ExclusiveDisjunction => '^',
+			BoolType::MaterialCondition => '>',
+			BoolType::LogicalEquivalence => '=',
+			BoolType::Existential => '∃',
+			BoolType::Universal => '∀',
+			BoolType::AtMost => '≤',
+			BoolType::AtLeast => '≥',
+			BoolType::Exactly => '#',
+			BoolType::Variable => {
+				panic!("no predefined symbol for '{}' type", boolean_type);
+			},
+		}
+	}
+
+	fn change_type(&mut self, new_type: BoolType) {
+		self.boolean_type = new_type;
+		self.op_symbol = Self::type_to_symbol(new_type);
+	}
+
+	fn new(c: char) -> Self {
+		let boolean_type = Self::symbol_to_type(c);
+		Self {
+			boolean_type,
+			op_symbol: c,
+			left: None,
+			right: None,
+			operands: None,
+		}
+	}
+
+	fn is_cardinality(&self) -> bool {
+		matches!(self.boolean_type,
+			BoolType::AtMost | BoolType::AtLeast | BoolType::Exactly)
+	}
+
+	/// Conjunction or disjunction holding its operands in a list instead of
+	/// a left and a right child (see `nary`).
+	pub fn is_nary(&self) -> bool {
+		matches!(self.boolean_type, BoolType::Conjunction | BoolType::Disjunction)
+			&& !self.operands().is_empty()
+	}
+
+	fn init_child<T: Iterator<Item = char>>(
+		&mut self,
+		formula: &mut Peekable<T>,
+	) -> Box<Self> {
+		if let Some(op) = formula.next() {
+			let mut child = Box::new(Self::new(op));
+			child.init_children(formula);
+			child
+		} else {
+			panic!("missing operand for '{}' operation", self.boolean_type);
+		}
+	}
+
+	/// Cardinality constraints are written "{ABC}2≤" (at most 2 of A, B and
+	/// C), the operands being any formula.
+	fn init_operands<T: Iterator<Item = char>>(&mut self, formula: &mut Peekable<T>) {
+		let mut digits: Vec<char> = vec![];
+		for c in formula.by_ref() {
+			match c {
+				'0'..='9' => digits.push(c),
+				'}' if !digits.is_empty() => break,
+				_ => panic!("invalid bound for '{}' operation", self.boolean_type),
+			}
+		}
+		let bound: String = digits.into_iter().rev().collect();
+		let bound = match bound.parse() {
+			Ok(bound) => bound,
+			Err(_) => panic!("invalid bound for '{}' operation", self.boolean_type),
+		};
+		let mut operands = vec![];
+		loop {
+			match formula.peek() {
+				Some('{') => {
+					formula.next();
+					break;
+				},
+				Some(_) => {
+					let operand = self.init_child(formula);
+					operands.push(*operand);
+				},
+				None => {
+					panic!("missing '{{' for '{}' operation", self.boolean_type);
+				},
+			}
+		}
+		operands.reverse();
+		self.set_operands(operands, bound);
+	}
+
+	fn init_children<T: Iterator<Item = char>>(&mut self, formula: &mut Peekable<T>) {
+		match self.boolean_type {
+			BoolType::Variable | BoolType::True | BoolType::False => (),
+			BoolType::Negation => {
+				self.left = Some(self.init_child(formula));
+			},
+			BoolType::Existential | BoolType::Universal => {
+				let variable = self.init_child(formula);
+				if variable.boolean_type != BoolType::Variable {
+					panic!("operand bound by '{}' operation is not a variable",
+						self.boolean_type);
+				}
+				self.right = Some(variable);
+				self.left = Some(self.init_child(formula));
+			},
+			BoolType::AtMost | BoolType::AtLeast | BoolType::Exactly => {
+				self.init_operands(formula);
+			},
+			_ => {
+				self.right = Some(self.init_child(formula));
+				self.left = Some(self.init_child(formula));
+			},
+		}
+	}
+
+	pub fn tree(formula: &str) -> Self {
+		let mut iter = formula.chars().rev().peekable();
+		let mut ast = if let Some(op) = iter.next() {
+			Self::new(op)
+		} else {
+			panic!("formula string is empty");
+		};
+		ast.init_children(&mut iter);
+		if let Some(_) = iter.next() {
+			panic!("unused operands in formula string");
+		}
+		ast
+	}
+
+	pub fn boolean_type(&self) -> BoolType {
+		self.boolean_type
+	}
+
+	pub fn op_symbol(&self) -> char {
+		self.op_symbol
+	}
+
+	pub fn left(&self) -> Option<&Self> {
+		self.left.as_deref()
+	}
+
+	pub fn right(&self) -> Option<&Self> {
+		self.right.as_deref()
+	}
+
+	/// Operands of a cardinality constraint or of an n-ary junction (empty
+	/// for every other node).
+	pub fn operands(&self) -> &[Self] {
+		self.operands.as_ref().map_or(&[], |operands| &operands.nodes)
+	}
+
+	fn operands_mut(&mut self) -> &mut [Self] {
+		match &mut self.operands {
+			Some(operands) => &mut operands.nodes,
+			None => &mut [],
+		}
+	}
+
+	fn set_operands(&mut self, nodes: Vec<Self>, bound: usize) {
+		self.operands = Some(Box::new(Operands { nodes, bound }));
+	}
+
+	fn into_operands(self) -> Vec<Self> {
+		self.operands.map_or(vec![], |operands| operands.nodes)
+	}
+
+	/// Number of true operands a cardinality constraint is compared to.
+	pub fn bound(&self) -> usize {
+		self.operands.as_ref().map_or(0, |operands| operands.bound)
+	}
+
+	/// Node reached by following `path` from this one, each step being 'l'
+	/// (left child) or 'r' (right child).
+	pub fn subtree_mut(&mut self, path: &str) -> Option<&mut Self> {
+		let mut node = self;
+		for step in path.chars() {
+			node = match step {
+				'l' => node.left.as_deref_mut()?,
+				'r' => node.right.as_deref_mut()?,
+				_ => panic!("'{}' is not a valid step (expected 'l' or 'r')", step),
+			};
+		}
+		Some(node)
+	}
+
+	fn children(&self) -> impl Iterator<Item = &Self> {
+		self.left.iter().chain(self.right.iter()).map(|node| &**node)
+			.chain(self.operands().iter())
+	}
+
+	/// Walk the tree with the given visitor, left child first. Returns
+	/// `Flow::Abort` if the visitor stopped the walk, `Flow::Continue` otherwise.
+	pub fn accept<V: Visitor>(&self, visitor: &mut V) -> Flow {
+		match visitor.enter(self) {
+			Flow::Abort => return Flow::Abort,
+			Flow::SkipChildren => (),
+			Flow::Continue => {
+				for node in self.children() {
+					if node.accept(visitor) == Flow::Abort {
+						return Flow::Abort
+					}
+				}
+			},
+		}
+		match visitor.leave(self) {
+			Flow::Abort => Flow::Abort,
+			_ => Flow::Continue,
+		}
+	}
+
+	/// Mutable version of `accept`.
+	pub fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> Flow {
+		match visitor.enter(self) {
+			Flow::Abort => return Flow::Abort,
+			Flow::SkipChildren => (),
+			Flow::Continue => {
+				let children = self.left.iter_mut().chain(self.right.iter_mut())
+					.map(|node| &mut **node)
+					.chain(self.operands.iter_mut()
+						.flat_map(|operands| operands.nodes.iter_mut()));
+				for node in children {
+					if node.accept_mut(visitor) == Flow::Abort {
+						return Flow::Abort
+					}
+				}
+			},
+		}
+		match visitor.leave(self) {
+			Flow::Abort => Flow::Abort,
+			_ => Flow::Continue,
+		}
+	}
+
+	/// Compute a value bottom-up: `op` is given each node along with the
+	/// values already computed for its left and right children (if any).
+	/// An n-ary node is folded as the right-handed chain of binary nodes it
+	/// stands for, `op` being given the n-ary node for each of them. There is
+	/// no such chain for a cardinality node, see `fold_operands` for these.
+	pub fn fold<T, F>(&self, op: &mut F) -> T
+	where F: FnMut(&Self, Option<T>, Option<T>) -> T {
+		if self.is_cardinality() {
+			panic!("'{}' operation has no binary children to fold", self.boolean_type);
+		}
+		if self.is_nary() {
+			let mut values: Vec<T> = self.operands().iter().map(|node| node.fold(op)).collect();
+			let last = values.pop().expect("n-ary node without operands");
+			return values.into_iter().rev()
+				.fold(last, |right, left| op(self, Some(left), Some(right)));
+		}
+		let left = self.left.as_ref().map(|node| node.fold(op));
+		let right = self.right.as_ref().map(|node| node.fold(op));
+		op(self, left, right)
+	}
+
+	/// Compute a value bottom-up like `fold`, but `op` is given the values
+	/// of every child, in order (the left one, the right one, then the
+	/// operands of an n-ary or cardinality node).
+	pub fn fold_operands<T, F>(&self, op: &mut F) -> T
+	where F: FnMut(&Self, Vec<T>) -> T {
+		let children: Vec<T> = self.children().map(|node| node.fold_operands(op)).collect();
+		op(self, children)
+	}
+
+	/// Length of the longest path from this node to a leaf (a lone variable
+	/// has a depth of 0).
+	pub fn depth(&self) -> usize {
+		self.fold_operands(&mut |_, children: Vec<usize>| {
+			children.into_iter().max().map_or(0, |depth| depth + 1)
+		})
+	}
+
+	/// Number of nodes in the tree.
+	pub fn size(&self) -> usize {
+		self.fold_operands(&mut |_, children: Vec<usize>| {
+			1 + children.into_iter().sum::<usize>()
+		})
+	}
+
+	/// Sorted list of the variables used in the formula, bound or not.
+	pub fn variables(&self) -> Vec<char> {
+		let mut variables = self.fold_operands(&mut |node, children: Vec<Vec<char>>| {
+			let mut vars: Vec<char> = children.concat();
+			if node.boolean_type == BoolType::Variable {
+				vars.push(node.op_symbol);
+			}
+			vars
+		});
+		variables.sort_unstable();
+		variables.dedup();
+		variables
+	}
+
+	/// Sorted list of the variables which are not bound by a quantifier.
+	pub fn free_variables(&self) -> Vec<char> {
+		let mut variables = self.fold_operands(&mut |node, children: Vec<Vec<char>>| {
+			match (node.boolean_type, &node.right) {
+				(BoolType::Existential | BoolType::Universal, Some(right)) => {
+					let mut vars = children.into_iter().next().unwrap_or_default();
+					vars.retain(|var| *var != right.op_symbol);
+					vars
+				},
+				(BoolType::Variable, _) => vec![node.op_symbol],
+				_ => children.concat(),
+			}
+		});
+		variables.sort_unstable();
+		variables.dedup();
+		variables
+	}
+
+	pub fn is_quantified(&self) -> bool {
+		matches!(self.boolean_type, BoolType::Existential | BoolType::Universal)
+			|| self.children().any(|node| node.is_quantified())
+	}
+
+	/// Check that every quantifier is at the top of the tree, above a
+	/// quantifier free formula (the matrix).
+	pub fn is_prenex(&self) -> bool {
+		match (self.boolean_type, &self.left) {
+			(BoolType::Existential | BoolType::Universal, Some(body)) => {
+				body.is_prenex()
+			},
+			_ => !self.is_quantified(),
+		}
+	}
+
+	/// Evaluate the formula with the given variable values. Panics if one of
+	/// the free variables of the formula has no value.
+	pub fn eval<A: Assignment + ?Sized>(&self, assignment: &A) -> bool {
+		if self.is_quantified() {
+			if self.is_prenex() {
+				return self.eval_qbf(assignment);
+			}
+			let mut copy = self.clone();
+			copy.eliminate_quantifiers();
+			return copy.eval(assignment);
+		}
+		self.eval_matrix(assignment)
+	}
+
+	fn eval_matrix<A: Assignment + ?Sized>(&self, assignment: &A) -> bool {
+		let count = || {
+			self.operands().iter().filter(|node| node.eval_matrix(assignment)).count()
+		};
+		match (self.boolean_type, &self.left, &self.right) {
+			(BoolType::True, None, None) => true,
+			(BoolType::False, None, None) => false,
+			(BoolType::Variable, None, None) => {
+				match assignment.value(self.op_symbol) {
+					Some(value) => value,
+					None => panic!("no value for variable '{}'", self.op_symbol),
+				}
+			},
+			(BoolType::Negation, Some(a), None) => !a.eval_matrix(assignment),
+			(BoolType::Conjunction, None, None) if self.is_nary() => {
+				self.operands().iter().all(|node| node.eval_matrix(assignment))
+			},
+			(BoolType::Disjunction, None, None) if self.is_nary() => {
+				self.operands().iter().any(|node| node.eval_matrix(assignment))
+			},
+			(BoolType::AtMost, None, None) => count() <= self.bound(),
+			(BoolType::AtLeast, None, None) => count() >= self.bound(),
+			(BoolType::Exactly, None, None) => count() == self.bound(),
+			(op, Some(a), Some(b)) => {
+				let (a, b) = (a.eval_matrix(assignment), b.eval_matrix(assignment));
+				match op {
+					BoolType::Conjunction => a && b,
+					BoolType::Disjunction => a || b,
+					BoolType::ExclusiveDisjunction => a != b,
+					BoolType::MaterialCondition => !a || b,
+					BoolType::LogicalEquivalence => a == b,
+					_ => panic!("invalid '{}' op", self.boolean_type),
+				}
+			},
+			_ => panic!("invalid '{}' op", self.boolean_type),
+		}
+	}
+
+	/// Evaluate a prenex formula by trying both values for each quantified
+	/// variable, from the outermost quantifier to the innermost.
+	pub fn eval_qbf<A: Assignment + ?Sized>(&self, assignment: &A) -> bool {
+		if !self.is_prenex() {
+			panic!("formula '{}' is not in prenex form", self.to_formula());
+		}
+		let mut overlay = Overlay { base: assignment, bound: vec![] };
+		self.eval_prefix(&mut overlay)
+	}
+
+	fn eval_prefix<A: Assignment + ?Sized>(&self, overlay: &mut Overlay<A>) -> bool {
+		match (self.boolean_type, &self.left, &self.right) {
+			(BoolType::Existential, Some(body), Some(variable))
+				| (BoolType::Universal, Some(body), Some(variable)) => {
+				let mut results = [false; 2];
+				for (index, value) in [false, true].iter().enumerate() {
+					overlay.bound.push((variable.op_symbol, *value));
+					results[index] = body.eval_prefix(overlay);
+					overlay.bound.pop();
+				}
+				if self.boolean_type == BoolType::Existential {
+					results[0] || results[1]
+				} else {
+					results[0] && results[1]
+				}
+			},
+			_ => self.eval_matrix(overlay),
+		}
+	}
+
+	/// Replace every quantifier by its Shannon expansion, innermost first:
+	/// ∃A. φ becomes φ[A:=0] ∨ φ[A:=1] and ∀A. φ becomes φ[A:=0] ∧ φ[A:=1].
+	/// Both halves are simplified on the way so the result has no constant
+	/// in it, unless it is itself a constant, and only one is kept when they
+	/// are the same formula.
+	pub fn eliminate_quantifiers(&mut self) {
+		self.eliminate_quantifiers_within(&Guard::new(Budget::default()))
+			.expect("expansion without budget");
+	}
+
+	/// Same as `eliminate_quantifiers`, the size of each expansion being
+	/// checked as soon as it is built.
+	fn eliminate_quantifiers_within(&mut self, guard: &Guard) -> Result<(), BudgetExceeded> {
+		if !self.is_quantified() { return Ok(()) };
+		*self = self.fold_operands(&mut |node, children: Vec<Result<Self, BudgetExceeded>>| {
+			let mut iter = children.into_iter().collect::<Result<Vec<Self>, _>>()?.into_iter();
+			match (node.boolean_type, iter.next(), iter.next()) {
+				(BoolType::Existential, Some(body), Some(variable))
+					| (BoolType::Universal, Some(body), Some(variable)) => {
+					let mut halves = [false, true].iter().map(|value| {
+						let mut varmap = HashMap::with_capacity(1);
+						varmap.insert(variable.op_symbol, *value);
+						body.partial_eval(&varmap)
+					});
+					let (low, high) = (halves.next().unwrap(), halves.next().unwrap());
+					let junction = if node.boolean_type == BoolType::Existential {
+						BoolType::Disjunction
+					} else {
+						BoolType::Conjunction
+					};
+					// the body may not depend on the variable: X ∨ X and X ∧ X are X
+					let expansion = if Canonical::new(&low) == Canonical::new(&high) {
+						low
+					} else {
+						Self::simplify_binary(junction, low, high)
+					};
+					guard.check(expansion.size(), 0)?;
+					Ok(expansion)
+				},
+				(_, left, right) => {
+					Ok(node.with_children(left.into_iter().chain(right).chain(iter).collect()))
+				},
+			}
+		})?;
+		Ok(())
+	}
+
+	pub fn constant(value: bool) -> Self {
+		Self::new(if value { '1' } else { '0' })
+	}
+
+	pub fn constant_value(&self) -> Option<bool> {
+		match self.boolean_type {
+			BoolType::True => Some(true),
+			BoolType::False => Some(false),
+			_ => None,
+		}
+	}
+
+	pub fn variable(name: char) -> Self {
+		let node = Self::new(name);
+		if node.boolean_type != BoolType::Variable {
+			panic!("'{}' is not a variable", name);
+		}
+		node
+	}
+
+	pub fn unary(op: char, operand: Self) -> Self {
+		let mut node = Self::new(op);
+		if node.boolean_type != BoolType::Negation {
+			panic!("'{}' is not a unary op", op);
+		}
+		node.left = Some(Box::new(operand));
+		node
+	}
+
+	pub fn binary(op: char, left: Self, right: Self) -> Self {
+		let mut node = Self::new(op);
+		match node.boolean_type {
+			BoolType::Variable | BoolType::True | BoolType::False
+				| BoolType::Negation => {
+				panic!("'{}' is not a binary op", op);
+			},
+			BoolType::Existential | BoolType::Universal
+				if right.boolean_type != BoolType::Variable => {
+				panic!("operand bound by '{}' operation is not a variable",
+					node.boolean_type);
+			},
+			_ => (),
+		}
+		node.left = Some(Box::new(left));
+		node.right = Some(Box::new(right));
+		node
+	}
+
+	pub fn exists(variable: char, body: Self) -> Self {
+		Self::binary('∃', body, Self::variable(variable))
+	}
+
+	pub fn forall(variable: char, body: Self) -> Self {
+		Self::binary('∀', body, Self::variable(variable))
+	}
+
+	/// Build a right-handed chain of conjunctions (True if empty).
+	pub fn conjunction(operands: Vec<Self>) -> Self {
+		Self::junction(operands, BoolType::Conjunction)
+	}
+
+	/// Build a right-handed chain of disjunctions (False if empty).
+	pub fn disjunction(operands: Vec<Self>) -> Self {
+		Self::junction(operands, BoolType::Disjunction)
+	}
+
+	fn junction(operands: Vec<Self>, target_type: BoolType) -> Self {
+		let symbol = Self::type_to_symbol(target_type);
+		let mut iter = operands.into_iter().rev();
+		match iter.next() {
+			None => Self::constant(target_type == BoolType::Conjunction),
+			Some(last) => iter.fold(last, |right, left| {
+				Self::binary(symbol, left, right)
+			}),
+		}
+	}
+
+	/// Conjunction ('&') or disjunction ('|') of any number of operands in a
+	/// single node. With less than two operands this is the neutral constant
+	/// or the operand itself, so an n-ary node always has two or more.
+	pub fn nary(op: char, mut operands: Vec<Self>) -> Self {
+		let mut node = Self::new(op);
+		if !matches!(node.boolean_type, BoolType::Conjunction | BoolType::Disjunction) {
+			panic!("'{}' is not a conjunction or disjunction op", op);
+		}
+		match operands.len() {
+			0 => Self::constant(node.boolean_type == BoolType::Conjunction),
+			1 => operands.pop().unwrap(),
+			_ => {
+				node.set_operands(operands, 0);
+				node
+			},
+		}
+	}
+
+	/// Turn every chain of conjunctions or disjunctions, whatever its shape,
+	/// into a single n-ary node. The rewrite rules (distribution, factoring,
+	/// rotations) only work on binary nodes, but everything else takes both.
+	pub fn to_nary(&mut self) {
+		*self = self.fold_operands(&mut |node, children: Vec<Self>| {
+			match node.boolean_type {
+				BoolType::Conjunction | BoolType::Disjunction => {
+					let mut operands = vec![];
+					for child in children {
+						if child.boolean_type == node.boolean_type && child.is_nary() {
+							operands.extend(child.into_operands());
+						} else {
+							operands.push(child);
+						}
+					}
+					Self::nary(node.op_symbol, operands)
+				},
+				_ => node.with_children(children),
+			}
+		});
+	}
+
+	/// Reverse of `to_nary`: every n-ary node becomes a right-handed chain.
+	pub fn to_binary(&mut self) {
+		*self = self.fold_operands(&mut |node, children: Vec<Self>| {
+			if node.is_nary() {
+				Self::junction(children, node.boolean_type)
+			} else {
+				node.with_children(children)
+			}
+		});
+	}
+
+	fn cardinality(op: char, bound: usize, operands: Vec<Self>) -> Self {
+		let mut node = Self::new(op);
+		node.set_operands(operands, bound);
+		node
+	}
+
+	/// At most `bound` of the operands are true.
+	pub fn at_most(bound: usize, operands: Vec<Self>) -> Self {
+		Self::cardinality('≤', bound, operands)
+	}
+
+	/// At least `bound` of the operands are true.
+	pub fn at_least(bound: usize, operands: Vec<Self>) -> Self {
+		Self::cardinality('≥', bound, operands)
+	}
 
-#[derive(Debug)]
-pub struct BoolNode {
-	boolean_type: BoolType,
-	op_symbol: char,
-	left: Option<Box<Self>>,
-	right: Option<Box<Self>>,
-}
+	/// Exactly `bound` of the operands are true.
+	pub fn exactly(bound: usize, operands: Vec<Self>) -> Self {
+		Self::cardinality('#', bound, operands)
+	}
 
-impl fmt::Display for BoolNode {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}", self.tree_string())
+	fn combinations(operands: &[Self], size: usize, guard: &Guard)
+		-> Result<Vec<Vec<Self>>, BudgetExceeded> {
+		if size == 0 { return Ok(vec![vec![]]) }
+		if operands.len() < size { return Ok(vec![]) }
+		guard.check(0, 0)?;
+		let mut result: Vec<Vec<Self>> = vec![];
+		for mut combination in Self::combinations(&operands[1..], size - 1, guard)? {
+			combination.insert(0, operands[0].clone());
+			result.push(combination);
+		}
+		result.append(&mut Self::combinations(&operands[1..], size, guard)?);
+		Ok(result)
 	}
-}
 
-impl Clone for BoolNode {
-	fn clone(&self) -> Self {
-		let mut ast = Self::new(self.op_symbol);
-		if let Some(left_node) = &self.left {
-			ast.left = Some(Box::new(*left_node.clone()));
+	/// Number of ways to pick `size` of `count` items, saturated at
+	/// usize::MAX.
+	fn binomial(count: usize, size: usize) -> usize {
+		if size > count { return 0 }
+		let size = size.min(count - size);
+		let mut result: u128 = 1;
+		for i in 0..size {
+			result = result * (count - i) as u128 / (i + 1) as u128;
+			if result > usize::MAX as u128 { return usize::MAX }
 		}
-		if let Some(right_node) = &self.right {
-			ast.right = Some(Box::new(*right_node.clone()));
+		result as usize
+	}
+
+	/// Equivalent formula of a cardinality constraint, without auxiliary
+	/// variables: "at most k" forbids every set of k + 1 true operands and
+	/// "at least k" requires one true operand in every set of n - k + 1. The
+	/// size of the result is binomial in the number of operands, see the
+	/// `encode` module for compact (equisatisfiable) encodings. The guard is
+	/// given the number of operands of the sets (a lower bound of the size)
+	/// before they are built.
+	fn expand(boolean_type: BoolType, bound: usize, operands: Vec<Self>, guard: &Guard)
+		-> Result<Self, BudgetExceeded> {
+		let count = operands.len();
+		let sets = |size: usize| {
+			guard.check(Self::binomial(count, size).saturating_mul(size), 0)?;
+			Self::combinations(&operands, size, guard)
+		};
+		let at_most = || -> Result<Self, BudgetExceeded> {
+			if bound >= count { return Ok(Self::constant(true)) }
+			Ok(Self::conjunction(sets(bound + 1)?
+				.into_iter()
+				.map(|set| Self::unary('!', Self::conjunction(set)))
+				.collect()))
+		};
+		let at_least = || -> Result<Self, BudgetExceeded> {
+			if bound == 0 { return Ok(Self::constant(true)) }
+			if bound > count { return Ok(Self::constant(false)) }
+			Ok(Self::conjunction(sets(count - bound + 1)?
+				.into_iter()
+				.map(Self::disjunction)
+				.collect()))
+		};
+		match boolean_type {
+			BoolType::AtMost => at_most(),
+			BoolType::AtLeast => at_least(),
+			_ => Ok(Self::simplify_binary(BoolType::Conjunction, at_most()?, at_least()?)),
 		}
-		ast
 	}
-}
 
-impl PartialEq for BoolNode {
-	/// This is not the smartest way to do this, but it was already done lol
-	fn eq(&self, other: &Self) -> bool {
-		let left_formula = self.to_formula();
-		let right_formula = other.to_formula();
-		if left_formula == right_formula { return true };
-		match (truth::table::<std::io::Stdout>(&left_formula, None),
-			truth::table::<std::io::Stdout>(&right_formula, None)) {
-			(Some(left_truth), Some(right_truth)) => {
-				left_truth == right_truth
+	/// Replace every cardinality constraint by its equivalent formula.
+	pub fn expand_cardinality(&mut self) {
+		self.expand_cardinality_within(&Guard::new(Budget::default()))
+			.expect("expansion without budget");
+	}
+
+	fn expand_cardinality_within(&mut self, guard: &Guard) -> Result<(), BudgetExceeded> {
+		let has_cardinality = self.fold_operands(&mut |node, children: Vec<bool>| {
+			node.is_cardinality() || children.contains(&true)
+		});
+		if !has_cardinality { return Ok(()) };
+		*self = self.fold_operands(&mut |node, children: Vec<Result<Self, BudgetExceeded>>| {
+			let children = children.into_iter().collect::<Result<Vec<Self>, _>>()?;
+			if node.is_cardinality() {
+				Self::expand(node.boolean_type, node.bound(), children, guard)
+			} else {
+				Ok(node.with_children(children))
+			}
+		})?;
+		Ok(())
+	}
+
+	/// Expand the cardinality constraints and the quantifiers (see
+	/// `expand_cardinality` and `eliminate_quantifiers`), giving up as soon
+	/// as the result is over the node limit of the budget or the time is out.
+	#[cfg(feature = "solver")]
+	pub(crate) fn expand_bounded(&mut self, budget: &Budget) -> Result<(), BudgetExceeded> {
+		self.expand_within(&Guard::new(*budget))
+	}
+
+	fn expand_within(&mut self, guard: &Guard) -> Result<(), BudgetExceeded> {
+		self.expand_cardinality_within(guard)?;
+		self.eliminate_quantifiers_within(guard)
+	}
+
+	fn flatten<'a>(&'a self, target_type: BoolType, operands: &mut Vec<&'a Self>) {
+		match (&self.left, &self.right) {
+			(Some(left), Some(right)) if self.boolean_type == target_type => {
+				left.flatten(target_type, operands);
+				right.flatten(target_type, operands);
+			},
+			_ if self.boolean_type == target_type && self.is_nary() => {
+				for operand in self.operands().iter() {
+					operand.flatten(target_type, operands);
+				}
 			},
-			_ => panic!("missing truth"),
+			_ => operands.push(self),
 		}
 	}
-}
 
-impl Eq for BoolNode {}
+	/// Top-level operands of a conjunction, whatever the shape of the tree
+	/// of conjunctions is. A formula which is not a conjunction is its own
+	/// single conjunct.
+	pub fn conjuncts(&self) -> Vec<&Self> {
+		let mut operands = vec![];
+		self.flatten(BoolType::Conjunction, &mut operands);
+		operands
+	}
 
-impl BoolNode {
-	fn symbol_to_type(c: char) -> BoolType {
-		match c {
-			'A' ..='Z' => BoolType::Variable,
-			'!' => BoolType::Negation,
-			'&' => BoolType::Conjunction,
-			'|' => BoolType::Disjunction,
-			'^' => BoolType::ExclusiveDisjunction,
-			'>' => BoolType::MaterialCondition,
-			'=' => BoolType::LogicalEquivalence,
-			_ => panic!("'{}' is not a valid op", c),
+	/// Same as `conjuncts` for disjunctions.
+	pub fn disjuncts(&self) -> Vec<&Self> {
+		let mut operands = vec![];
+		self.flatten(BoolType::Disjunction, &mut operands);
+		operands
+	}
+
+	/// Replace the variables known by the assignment with their value and
+	/// simplify the constants away. The result is either a single constant
+	/// or a formula without any constant in it.
+	pub fn partial_eval<A: Assignment + ?Sized>(&self, assignment: &A) -> Self {
+		if self.is_quantified() {
+			let mut copy = self.clone();
+			copy.eliminate_quantifiers();
+			return copy.partial_eval(assignment);
 		}
+		self.fold_operands(&mut |node, children: Vec<Self>| {
+			if node.is_cardinality() {
+				return Self::simplify_cardinality(node.boolean_type,
+					node.bound(), children);
+			}
+			if node.is_nary() {
+				return Self::simplify_nary(node.boolean_type, children);
+			}
+			let mut iter = children.into_iter();
+			match (node.boolean_type, iter.next(), iter.next()) {
+				(BoolType::Variable, None, None) => {
+					match assignment.value(node.op_symbol) {
+						Some(value) => Self::constant(value),
+						None => Self::new(node.op_symbol),
+					}
+				},
+				(BoolType::True | BoolType::False, None, None) => {
+					Self::new(node.op_symbol)
+				},
+				(BoolType::Negation, Some(a), None) => {
+					match a.constant_value() {
+						Some(value) => Self::constant(!value),
+						None => Self::unary('!', a),
+					}
+				},
+				(boolean_type, Some(a), Some(b)) => {
+					Self::simplify_binary(boolean_type, a, b)
+				},
+				_ => panic!("invalid '{}' op", node.boolean_type),
+			}
+		})
 	}
 
-	fn type_to_symbol(boolean_type: BoolType) -> char {
-		match boolean_type {
-			BoolType::Negation => '!',
-			BoolType::Conjunction => '&',
-			BoolType::Disjunction => '|',
-			BoolType::ExclusiveDisjunction => '^',
-			BoolType::MaterialCondition => '>',
-			BoolType::LogicalEquivalence => '=',
-			BoolType::Variable => {
-				panic!("no predefined symbol for '{}' type", boolean_type);
+	/// Constant folding on the whole tree.
+	pub fn simplify(&self) -> Self {
+		self.partial_eval(&HashMap::new())
+	}
+
+	fn simplify_binary(boolean_type: BoolType, a: Self, b: Self) -> Self {
+		let op = Self::type_to_symbol(boolean_type);
+		let negate = |node: Self| {
+			match (node.constant_value(), node.boolean_type) {
+				(Some(value), _) => Self::constant(!value),
+				(None, BoolType::Negation) => *node.left.unwrap(),
+				_ => Self::unary('!', node),
+			}
+		};
+		match (boolean_type, a.constant_value(), b.constant_value()) {
+			(_, None, None) => Self::binary(op, a, b),
+			(BoolType::Conjunction, Some(false), _)
+				| (BoolType::Conjunction, _, Some(false)) => Self::constant(false),
+			(BoolType::Conjunction, Some(true), _) => b,
+			(BoolType::Conjunction, _, Some(true)) => a,
+			(BoolType::Disjunction, Some(true), _)
+				| (BoolType::Disjunction, _, Some(true)) => Self::constant(true),
+			(BoolType::Disjunction, Some(false), _) => b,
+			(BoolType::Disjunction, _, Some(false)) => a,
+			(BoolType::ExclusiveDisjunction, Some(false), _) => b,
+			(BoolType::ExclusiveDisjunction, Some(true), _) => negate(b),
+			(BoolType::ExclusiveDisjunction, _, Some(false)) => a,
+			(BoolType::ExclusiveDisjunction, _, Some(true)) => negate(a),
+			(BoolType::MaterialCondition, Some(false), _)
+				| (BoolType::MaterialCondition, _, Some(true)) => {
+				Self::constant(true)
 			},
+			(BoolType::MaterialCondition, Some(true), _) => b,
+			(BoolType::MaterialCondition, _, Some(false)) => negate(a),
+			(BoolType::LogicalEquivalence, Some(true), _) => b,
+			(BoolType::LogicalEquivalence, Some(false), _) => negate(b),
+			(BoolType::LogicalEquivalence, _, Some(true)) => a,
+			(BoolType::LogicalEquivalence, _, Some(false)) => negate(a),
+			_ => panic!("invalid '{}' op", boolean_type),
 		}
 	}
 
-	fn change_type(&mut self, new_type: BoolType) {
-		self.boolean_type = new_type;
-		self.op_symbol = Self::type_to_symbol(new_type);
+	/// Remove the neutral constants of an n-ary junction, or replace it by
+	/// its absorbing constant if it has one.
+	fn simplify_nary(boolean_type: BoolType, children: Vec<Self>) -> Self {
+		let absorbing = boolean_type == BoolType::Disjunction;
+		if children.iter().any(|child| child.constant_value() == Some(absorbing)) {
+			return Self::constant(absorbing);
+		}
+		let operands: Vec<Self> = children.into_iter()
+			.filter(|child| child.constant_value().is_none())
+			.collect();
+		Self::nary(Self::type_to_symbol(boolean_type), operands)
 	}
 
-	fn new(c: char) -> Self {
-		let boolean_type = Self::symbol_to_type(c);
-		Self { boolean_type, op_symbol: c, left: None, right: None }
+	/// Remove the constant operands of a cardinality constraint, which may
+	/// decide it right away.
+	fn simplify_cardinality(
+		boolean_type: BoolType,
+		bound: usize,
+		children: Vec<Self>,
+	) -> Self {
+		let count = children.iter()
+			.filter(|child| child.constant_value() == Some(true))
+			.count();
+		let operands: Vec<Self> = children.into_iter()
+			.filter(|child| child.constant_value().is_none())
+			.collect();
+		let (at_least, at_most) = match boolean_type {
+			BoolType::AtMost => (0, bound),
+			BoolType::AtLeast => (bound, usize::MAX),
+			_ => (bound, bound),
+		};
+		if count > at_most || count + operands.len() < at_least {
+			return Self::constant(false);
+		}
+		let at_most = at_most.saturating_sub(count);
+		let at_least = at_least.saturating_sub(count);
+		match (at_least, at_most >= operands.len()) {
+			(0, true) => Self::constant(true),
+			(0, false) => Self::at_most(at_most, operands),
+			(_, true) => Self::at_least(at_least, operands),
+			_ => Self::exactly(at_least, operands),
+		}
 	}
 
-	fn init_child<T: Iterator<Item = char>>(&mut self, formula: &mut T) -> Box<Self> {
-		if let Some(op) = formula.next() {
-			let mut child = Box::new(Self::new(op));
-			child.init_children(formula);
-			child
+	fn with_children(&self, children: Vec<Self>) -> Self {
+		let mut node = Self::new(self.op_symbol);
+		if self.is_cardinality() || self.is_nary() {
+			node.set_operands(children, self.bound());
 		} else {
-			panic!("missing operand for '{}' operation", self.boolean_type);
+			let mut iter = children.into_iter();
+			node.left = iter.next().map(Box::new);
+			node.right = iter.next().map(Box::new);
 		}
+		node
 	}
 
-	fn init_children<T: Iterator<Item = char>>(&mut self, formula: &mut T) {
-		match self.boolean_type {
-			BoolType::Variable => (),
-			BoolType::Negation => {
-				self.left = Some(self.init_child(formula));
+	/// Replace every occurrence of `variable` by a copy of `formula`.
+	pub fn substitute(&self, variable: char, formula: &Self) -> Self {
+		let mut substitutions = HashMap::with_capacity(1);
+		substitutions.insert(variable, formula.clone());
+		self.substitute_all(&substitutions)
+	}
+
+	/// Replace the variables of the map by their formula simultaneously, so
+	/// the variables introduced by a substitution are never substituted
+	/// themselves (A:=B and B:=A swaps A and B). The other variables are kept.
+	/// Bound variables are left untouched, and they are renamed when they
+	/// would capture a free variable of a substituted formula.
+	pub fn substitute_all(&self, substitutions: &HashMap<char, Self>) -> Self {
+		match (self.boolean_type, &self.left, &self.right) {
+			(BoolType::Variable, _, _) => {
+				match substitutions.get(&self.op_symbol) {
+					Some(formula) => formula.clone(),
+					None => self.clone(),
+				}
 			},
-			_ => {
-				self.right = Some(self.init_child(formula));
-				self.left = Some(self.init_child(formula));
+			(BoolType::Existential, Some(body), Some(variable))
+				| (BoolType::Universal, Some(body), Some(variable)) => {
+				let mut bound = variable.op_symbol;
+				let mut inner = substitutions.clone();
+				inner.remove(&bound);
+				let free = body.free_variables();
+				let captures = inner.iter().any(|(var, formula)| {
+					free.contains(var) && formula.free_variables().contains(&bound)
+				});
+				if captures {
+					let mut used = body.variables();
+					for formula in inner.values() {
+						used.append(&mut formula.variables());
+					}
+					bound = match ('A'..='Z').find(|c| !used.contains(c)) {
+						Some(fresh) => fresh,
+						None => panic!("no variable left to rename bound '{}'",
+							variable.op_symbol),
+					};
+					inner.insert(variable.op_symbol, Self::new(bound));
+				}
+				Self::binary(self.op_symbol, body.substitute_all(&inner),
+					Self::new(bound))
 			},
+			_ => self.with_children(
+				self.children()
+					.map(|node| node.substitute_all(substitutions))
+					.collect()
+			),
 		}
 	}
 
-	pub fn tree(formula: &str) -> Self {
-		let mut iter = formula.chars().rev();
-		let mut ast = if let Some(op) = iter.next() {
-			Self::new(op)
-		} else {
-			panic!("formula string is empty");
-		};
-		ast.init_children(&mut iter);
-		if let Some(_) = iter.next() {
-			panic!("unused operands in formula string");
+	/// Rename the variables of the map simultaneously (which means that
+	/// permutations work as expected).
+	pub fn rename(&self, renaming: &HashMap<char, char>) -> Self {
+		for to in renaming.values() {
+			if Self::symbol_to_type(*to) != BoolType::Variable {
+				panic!("cannot rename to '{}' since it is not a variable", to);
+			}
 		}
-		ast
+		let substitutions: HashMap<char, Self> = renaming.iter()
+			.map(|(from, to)| (*from, Self::new(*to)))
+			.collect();
+		self.substitute_all(&substitutions)
+	}
+
+	/// Plug formulas into a template. Unlike `substitute_all` every variable
+	/// of the template must be given a formula, the template acting as a
+	/// function of its variables.
+	pub fn compose(template: &Self, parts: &HashMap<char, Self>) -> Self {
+		for variable in template.free_variables() {
+			if !parts.contains_key(&variable) {
+				panic!("no formula given for template variable '{}'", variable);
+			}
+		}
+		template.substitute_all(parts)
 	}
 
 	pub fn pre_order(&mut self, op: impl Fn(&mut Self) + Copy) {
@@ -146,6 +1441,9 @@ impl BoolNode {
 		if let Some(right_node) = &mut self.right {
 			right_node.pre_order(op);
 		}
+		for operand in self.operands_mut().iter_mut() {
+			operand.pre_order(op);
+		}
 	}
 
 	pub fn in_order(&mut self, op: impl Fn(&mut Self) + Copy) {
@@ -165,6 +1463,9 @@ impl BoolNode {
 		if let Some(right_node) = &mut self.right {
 			right_node.post_order(op);
 		}
+		for operand in self.operands_mut().iter_mut() {
+			operand.post_order(op);
+		}
 		op(self);
 	}
 
@@ -496,6 +1797,7 @@ impl BoolNode {
 			self.op_symbol = grand_child.op_symbol;
 			std::mem::swap(&mut self.left, &mut grand_child.left);
 			std::mem::swap(&mut self.right, &mut grand_child.right);
+			std::mem::swap(&mut self.operands, &mut grand_child.operands);
 			self.eliminate_double_negation();
 		}
 	}
@@ -517,6 +1819,15 @@ impl BoolNode {
 				BoolType::Conjunction
 			};
 			self.change_type(new_type);
+			if child.is_nary() {
+				let operands = child.into_operands().into_iter().map(|operand| {
+					let mut negation = Self::unary('!', operand);
+					negation.eliminate_double_negation();
+					negation
+				}).collect();
+				self.set_operands(operands, 0);
+				return
+			}
 			let mut new_left = Box::new(Self::new('!'));
 			let mut new_right = Box::new(Self::new('!'));
 			std::mem::swap(&mut new_left.left, &mut child.left);
@@ -552,7 +1863,7 @@ impl BoolNode {
 
 	pub fn negation_normal_form(&self) -> bool {
 		match self.boolean_type {
-			BoolType::Variable => true,
+			BoolType::Variable | BoolType::True | BoolType::False => true,
 			BoolType::Negation => {
 				if let (Some(child), None) = (&self.left, &self.right) {
 					child.boolean_type == BoolType::Variable
@@ -563,6 +1874,8 @@ impl BoolNode {
 			BoolType::Disjunction | BoolType::Conjunction => {
 				if let (Some(left), Some(right)) = (&self.left, &self.right) {
 					left.negation_normal_form() && right.negation_normal_form()
+				} else if self.is_nary() {
+					self.operands().iter().all(Self::negation_normal_form)
 				} else {
 					panic!("invalid '{}' op", self.boolean_type);
 				}
@@ -571,125 +1884,321 @@ impl BoolNode {
 		}
 	}
 
-	fn cnf_check(&self, accept_conjunctions: bool) -> bool {
-		match (self.boolean_type, accept_conjunctions) {
-			(BoolType::Conjunction, true) => {
-				if let (Some(left), Some(right)) = (&self.left, &self.right) {
-					left.cnf_check(false) && right.cnf_check(true)
-				} else {
-					panic!("invalid '{}' op", self.boolean_type);
-				}
-			},
-			(BoolType::Conjunction, false) => false,
-			(BoolType::Disjunction, _) => {
-				if let (Some(left), Some(right)) = (&self.left, &self.right) {
-					left.cnf_check(false) && right.cnf_check(false)
-				} else {
-					panic!("invalid '{}' op", self.boolean_type);
-				}
-			},
-			(BoolType::Variable, _) => true,
-			(BoolType::Negation, _) => true,
-			_ => panic!("invalid op '{}' in CNF", self.boolean_type),
-		}
+	fn cnf_shape(&self) -> CnfShape {
+		self.fold_operands(&mut |node, children: Vec<CnfShape>| {
+			match (node.boolean_type, children.as_slice()) {
+				(BoolType::Variable | BoolType::True | BoolType::False, _) => {
+					CnfShape::Literal
+				},
+				(BoolType::Negation, _) => CnfShape::Literal,
+				(BoolType::Disjunction, [_, _, ..]) => {
+					let clause = children.iter().all(|shape| {
+						matches!(shape, CnfShape::Literal | CnfShape::Clause)
+					});
+					if clause { CnfShape::Clause } else { CnfShape::Invalid }
+				},
+				// only the last operand of a conjunction may be one itself
+				(BoolType::Conjunction, [init @ .., last]) if !init.is_empty() => {
+					let invalid = *last == CnfShape::Invalid
+						|| init.iter().any(|shape| {
+							matches!(shape, CnfShape::Invalid | CnfShape::Cnf)
+						});
+					if invalid { CnfShape::Invalid } else { CnfShape::Cnf }
+				},
+				(BoolType::Disjunction | BoolType::Conjunction, _) => {
+					panic!("invalid '{}' op", node.boolean_type);
+				},
+				_ => panic!("invalid op '{}' in CNF", node.boolean_type),
+			}
+		})
 	}
 
 	pub fn conjunctive_normal_form(&self) -> bool {
-		self.negation_normal_form() && self.cnf_check(true)
+		self.negation_normal_form() && self.cnf_shape() != CnfShape::Invalid
 	}
 
-	fn has_left(&self) -> bool {
-		match self.left {
-			Some(_) => true,
-			_ => false,
+	/// The negation of a DNF is a CNF once the negation is pushed down.
+	pub fn disjunctive_normal_form(&self) -> bool {
+		if !self.negation_normal_form() { return false };
+		let mut dual = Self::unary('!', self.clone());
+		dual.pre_order(Self::replace_junction_negation);
+		dual.pre_order(Self::eliminate_double_negation);
+		dual.conjunctive_normal_form()
+	}
+
+	fn node_label(&self) -> String {
+		match self.boolean_type {
+			BoolType::Variable => format!("{}({})", self.boolean_type, self.op_symbol),
+			BoolType::AtMost | BoolType::AtLeast | BoolType::Exactly => {
+				format!("{}({})", self.boolean_type, self.bound())
+			},
+			_ => self.boolean_type.to_string(),
 		}
 	}
 
-	fn has_right(&self) -> bool {
-		match self.right {
-			Some(_) => true,
-			_ => false,
+	/// Children in display order: right before left, or the operands.
+	fn display_children(&self) -> Vec<&Self> {
+		if self.is_cardinality() || self.is_nary() {
+			self.operands().iter().collect()
+		} else {
+			self.right.iter().chain(self.left.iter()).map(|node| &**node).collect()
 		}
 	}
 
-	fn node_string(mut tree: &mut String,
-		node_opt: &Option<Box<Self>>, mut padding: String,
+	fn node_string(tree: &mut String, node: &Self, mut padding: String,
 		pointer: &str, has_left_sibling: bool) {
-		if let Some(node) = node_opt {
-			tree.push_str(&format!("\n{}{}{}",
-				padding, pointer, node.boolean_type));
-			if node.boolean_type == BoolType::Variable {
-				tree.push_str(&format!("({})", node.op_symbol));
-			}
-			padding.push_str(if has_left_sibling { "│  " } else { "   " });
-			let pointer_left = "└──";
-			let pointer_right = if node.has_left() { "├──" } else { "└──" };
-			Self::node_string(&mut tree, &node.right, padding.clone(),
-				pointer_right, node.has_left());
-			Self::node_string(&mut tree, &node.left, padding,
-				pointer_left, false);
+		tree.push_str(&format!("\n{}{}{}", padding, pointer, node.node_label()));
+		padding.push_str(if has_left_sibling { "│  " } else { "   " });
+		node.children_string(tree, padding);
+	}
+
+	fn children_string(&self, tree: &mut String, padding: String) {
+		let children = self.display_children();
+		for (index, child) in children.iter().enumerate() {
+			let is_last = index + 1 == children.len();
+			let pointer = if is_last { "└──" } else { "├──" };
+			Self::node_string(tree, child, padding.clone(), pointer, !is_last);
 		}
 	}
 
 	fn tree_string(&self) -> String {
-		let mut tree = String::new();
-		let pointer_left = "└──";
-		let pointer_right = if self.has_left() { "├──" } else { "└──" };
-
-		tree.push_str(&self.boolean_type.to_string());
-		if self.boolean_type == BoolType::Variable {
-			tree.push_str(&format!("({})", self.op_symbol));
-		}
-		Self::node_string(&mut tree, &self.right, String::new(),
-			pointer_right, self.has_left());
-		Self::node_string(&mut tree, &self.left, String::new(),
-			pointer_left, false);
+		let mut tree = self.node_label();
+		self.children_string(&mut tree, String::new());
 		tree
 	}
 
+	/// Total order on the structure of the formulas: by operation, then
+	/// symbol and bound, then operands from the first one.
+	pub fn structural_cmp(&self, other: &Self) -> Ordering {
+		self.boolean_type.cmp(&other.boolean_type)
+			.then(self.op_symbol.cmp(&other.op_symbol))
+			.then(self.bound().cmp(&other.bound()))
+			.then_with(|| {
+				let mut others = other.children();
+				for child in self.children() {
+					match others.next() {
+						Some(other_child) => match child.structural_cmp(other_child) {
+							Ordering::Equal => continue,
+							ordering => return ordering,
+						},
+						None => return Ordering::Greater,
+					}
+				}
+				if others.next().is_some() { Ordering::Less } else { Ordering::Equal }
+			})
+	}
+
+	fn hash_structure<H: Hasher>(&self, state: &mut H) {
+		self.boolean_type.hash(state);
+		self.op_symbol.hash(state);
+		self.bound().hash(state);
+		for child in self.children() {
+			child.hash_structure(state);
+		}
+	}
+
+	/// Put the formula in canonical form: chains of the same commutative and
+	/// associative operation (conjunction, disjunction, exclusive disjunction
+	/// and equivalence) are flattened, their operands sorted with
+	/// `structural_cmp` and rebuilt as a right-handed tree. The operands of
+	/// cardinality constraints are sorted too. N-ary nodes are made binary.
+	pub fn canonicalize(&mut self) {
+		self.to_binary();
+		self.sort_operands();
+	}
+
+	fn sort_operands(&mut self) {
+		if let Some(left_node) = &mut self.left {
+			left_node.sort_operands();
+		}
+		if let Some(right_node) = &mut self.right {
+			right_node.sort_operands();
+		}
+		for operand in self.operands_mut().iter_mut() {
+			operand.sort_operands();
+		}
+		match self.boolean_type {
+			BoolType::Conjunction | BoolType::Disjunction
+				| BoolType::ExclusiveDisjunction | BoolType::LogicalEquivalence => {
+				let boolean_type = self.boolean_type;
+				let mut operands: Vec<Box<Self>> = self.get_operands(boolean_type)
+					.into_iter()
+					.map(|op| op.expect("empty option operand"))
+					.collect();
+				operands.sort_by(|a, b| a.structural_cmp(b));
+				self.build_right_handed_tree_from_operand_list(operands, boolean_type);
+			},
+			BoolType::AtMost | BoolType::AtLeast | BoolType::Exactly => {
+				self.operands_mut().sort_by(|a, b| a.structural_cmp(b));
+			},
+			_ => (),
+		}
+	}
+
 	pub fn to_formula(&self) -> String {
-		let mut formula = String::new();
-		fn add_node(node: &BoolNode, formula: &mut String) {
-			if let Some(left) = &node.left { add_node(left, formula); }
-			if let Some(right) = &node.right { add_node(right, formula); }
-			formula.push(node.op_symbol);
+		let mut writer = FormulaWriter { formula: String::new() };
+		self.accept(&mut writer);
+		writer.formula
+	}
+
+	fn infix_operand(&self) -> String {
+		match self.boolean_type {
+			BoolType::Variable | BoolType::True | BoolType::False
+				| BoolType::Negation => self.to_infix(),
+			_ => format!("({})", self.to_infix()),
+		}
+	}
+
+	/// Usual mathematical notation of the formula, with parentheses around
+	/// every operand which is not a literal.
+	pub fn to_infix(&self) -> String {
+		match (self.boolean_type, &self.left, &self.right) {
+			(BoolType::True, _, _) => String::from("⊤"),
+			(BoolType::False, _, _) => String::from("⊥"),
+			(BoolType::Variable, _, _) => self.op_symbol.to_string(),
+			(BoolType::Negation, Some(operand), None) => {
+				format!("¬{}", operand.infix_operand())
+			},
+			(BoolType::Existential, Some(body), Some(variable))
+				| (BoolType::Universal, Some(body), Some(variable)) => {
+				format!("{}{}. {}", self.op_symbol, variable.op_symbol,
+					body.infix_operand())
+			},
+			(BoolType::AtMost | BoolType::AtLeast | BoolType::Exactly, _, _) => {
+				let operands: Vec<String> = self.operands().iter()
+					.map(|operand| operand.to_infix())
+					.collect();
+				let symbol = if self.boolean_type == BoolType::Exactly {
+					'='
+				} else {
+					self.op_symbol
+				};
+				format!("#{{{}}} {} {}", operands.join(", "), symbol, self.bound())
+			},
+			(BoolType::Conjunction | BoolType::Disjunction, None, None)
+				if self.is_nary() => {
+				let operands: Vec<String> = self.operands().iter()
+					.map(|operand| operand.infix_operand())
+					.collect();
+				let symbol = if self.boolean_type == BoolType::Conjunction {
+					" ∧ "
+				} else {
+					" ∨ "
+				};
+				operands.join(symbol)
+			},
+			(_, Some(left), Some(right)) => {
+				let symbol = match self.boolean_type {
+					BoolType::Conjunction => '∧',
+					BoolType::Disjunction => '∨',
+					BoolType::ExclusiveDisjunction => '⊕',
+					BoolType::MaterialCondition => '⇒',
+					_ => '⇔',
+				};
+				format!("{} {} {}", left.infix_operand(), symbol,
+					right.infix_operand())
+			},
+			_ => panic!("invalid '{}' op", self.boolean_type),
+		}
+	}
+
+	/// Same as `pre_order` but every change is given to the tracer.
+	fn traced_pre_order(&mut self, rule: &'static str, op: fn(&mut Self),
+		tracer: &mut Tracer) {
+		tracer.apply(rule, self, op);
+		if let Some(left_node) = &mut self.left {
+			tracer.path.push('l');
+			left_node.traced_pre_order(rule, op, tracer);
+			tracer.path.pop();
 		}
-		add_node(self, &mut formula);
-		formula
+		if let Some(right_node) = &mut self.right {
+			tracer.path.push('r');
+			right_node.traced_pre_order(rule, op, tracer);
+			tracer.path.pop();
+		}
+		// only met untraced, `traced` makes the formula binary first
+		for operand in self.operands_mut().iter_mut() {
+			operand.traced_pre_order(rule, op, tracer);
+		}
+	}
+
+	fn nnf(&mut self, tracer: &mut Tracer) {
+		tracer.apply("expand_cardinality", self, Self::expand_cardinality);
+		tracer.apply("eliminate_quantifiers", self, Self::eliminate_quantifiers);
+		self.traced_pre_order("replace_logical_equivalence",
+			Self::replace_logical_equivalence, tracer);
+		self.traced_pre_order("replace_material_condition",
+			Self::replace_material_condition, tracer);
+		self.traced_pre_order("replace_exclusive_disjunction",
+			Self::replace_exclusive_disjunction, tracer);
+		self.traced_pre_order("eliminate_double_negation",
+			Self::eliminate_double_negation, tracer);
+		self.traced_pre_order("replace_junction_negation",
+			Self::replace_junction_negation, tracer);
 	}
 
 	pub fn to_nnf(&mut self) {
-		self.pre_order(Self::replace_logical_equivalence);
-		self.pre_order(Self::replace_material_condition);
-		self.pre_order(Self::replace_exclusive_disjunction);
-		self.pre_order(Self::eliminate_double_negation);
-		self.pre_order(Self::replace_junction_negation);
+		self.nnf(&mut Tracer::new(false));
+	}
+
+	fn build_right_handed_tree(&mut self) {
+		let ops = self.get_operands(self.boolean_type);
+		let operands: Vec<Box<Self>> = ops.into_iter()
+			.map(|op| op.unwrap())
+			.collect();
+		self.build_right_handed_tree_from_operand_list(operands, self.boolean_type);
 	}
 
-	fn cnf(&mut self) {
-		let mut operands: Vec<Box<Self>> = vec![];
+	/// Flatten the clauses of a right-handed chain of `outer` junctions.
+	fn build_clauses(&mut self, outer: BoolType, tracer: &mut Tracer) {
+		match (&mut self.left, &mut self.right) {
+			(Some(l), Some(r)) if self.boolean_type == outer => {
+				tracer.path.push('l');
+				l.build_clauses(outer, tracer);
+				tracer.path.pop();
+				tracer.path.push('r');
+				r.build_clauses(outer, tracer);
+				tracer.path.pop();
+			},
+			(Some(_), Some(_)) => {
+				tracer.apply("build_right_handed_tree", self, Self::build_right_handed_tree);
+			},
+			_ => (),
+		}
+	}
 
+	/// Distribute the `inner` junction over the other one until it only has
+	/// literals as operands (disjunction for CNF, conjunction for DNF), one
+	/// rewrite rule at a time. This is what traced conversions do, and it
+	/// gives the same result as `clause_lists`.
+	fn normal_form(&mut self, inner: BoolType, tracer: &mut Tracer) {
 		match (self.boolean_type, &mut self.left, &mut self.right) {
-			(BoolType::Variable | BoolType::Negation, _, _) => (),
+			(
+				BoolType::Variable | BoolType::Negation
+					| BoolType::True | BoolType::False,
+				_,
+				_
+			) => (),
 			(
 				BoolType::Conjunction | BoolType::Disjunction,
 				Some(l),
 				Some(r)
 			) => {
-				l.cnf();
-				r.cnf();
-				if self.boolean_type == BoolType::Disjunction {
-					self.distribute(BoolType::Disjunction);
-					if self.boolean_type != BoolType::Disjunction {
+				tracer.path.push('l');
+				l.normal_form(inner, tracer);
+				tracer.path.pop();
+				tracer.path.push('r');
+				r.normal_form(inner, tracer);
+				tracer.path.pop();
+				if self.boolean_type == inner {
+					tracer.apply("distribute", self, |node| node.distribute(inner));
+					if self.boolean_type != inner {
+						let outer = self.boolean_type;
+						self.build_clauses(outer, tracer);
 						return
 					}
 				}
-				let ops = self.get_operands(self.boolean_type);
-				for op in ops { operands.push(op.unwrap()); }
-				self.build_right_handed_tree_from_operand_list(
-					operands,
-					self.boolean_type,
-				);
+				tracer.apply("build_right_handed_tree", self, Self::build_right_handed_tree);
 			},
 			(BoolType::Conjunction | BoolType::Disjunction, _, _) => {
 				panic!("missing operand for '{}' operation", self.boolean_type);
@@ -698,8 +2207,190 @@ impl BoolNode {
 		}
 	}
 
+	/// Literals of each clause of the normal form of a formula in NNF, the
+	/// `inner` junction being distributed over the other one (disjunction for
+	/// CNF, conjunction for DNF). Binary and n-ary nodes are both taken.
+	fn clause_lists(&self, inner: BoolType, guard: &Guard)
+		-> Result<Vec<Vec<Self>>, BudgetExceeded> {
+		match self.boolean_type {
+			BoolType::Variable | BoolType::Negation
+				| BoolType::True | BoolType::False => Ok(vec![vec![self.clone()]]),
+			BoolType::Conjunction | BoolType::Disjunction => {
+				let mut children = self.children();
+				let mut clauses = match children.next() {
+					Some(child) => child.clause_lists(inner, guard)?,
+					None => panic!("missing operand for '{}' operation",
+						self.boolean_type),
+				};
+				for child in children {
+					let mut others = child.clause_lists(inner, guard)?;
+					if self.boolean_type != inner {
+						clauses.append(&mut others);
+						continue
+					}
+					let (nodes, count) = if guard.counts_size() {
+						Self::product_size(&clauses, &others)
+					} else {
+						(0, 0)
+					};
+					guard.check(nodes, count)?;
+					clauses = clauses.iter().flat_map(|clause| {
+						others.iter().map(move |other| {
+							clause.iter().chain(other.iter()).cloned().collect()
+						})
+					}).collect();
+				}
+				Ok(clauses)
+			},
+			_ => panic!("unexpected op '{}'", self.boolean_type),
+		}
+	}
+
+	/// Number of nodes and of clauses of the (binary) normal form made of
+	/// every clause of `left` joined with every clause of `right`.
+	fn product_size(left: &[Vec<Self>], right: &[Vec<Self>]) -> (usize, usize) {
+		let size = |clauses: &[Vec<Self>]| -> usize {
+			clauses.iter()
+				.map(|clause| clause.iter().map(|lit| lit.size()).sum::<usize>()
+					+ clause.len() - 1)
+				.fold(0, usize::saturating_add)
+		};
+		let (a, b) = (left.len(), right.len());
+		let clauses = a.saturating_mul(b);
+		let nodes = b.saturating_mul(size(left))
+			.saturating_add(a.saturating_mul(size(right)))
+			.saturating_add(clauses.saturating_mul(2));
+		(nodes, clauses)
+	}
+
+	/// N-ary normal form of a formula in NNF: an n-ary `outer` junction of
+	/// n-ary `inner` junctions of literals.
+	fn nary_normal_form(&mut self, inner: BoolType, guard: &Guard)
+		-> Result<(), BudgetExceeded> {
+		let outer = if inner == BoolType::Disjunction { '&' } else { '|' };
+		let inner_symbol = Self::type_to_symbol(inner);
+		let clauses = self.clause_lists(inner, guard)?;
+		*self = Self::nary(outer, clauses.into_iter()
+			.map(|clause| Self::nary(inner_symbol, clause))
+			.collect());
+		Ok(())
+	}
+
+	/// Number of nodes the negation normal form of the formula (and of its
+	/// negation) has, without building it. Cardinality constraints and
+	/// quantifiers must have been expanded before.
+	fn nnf_size(&self) -> (usize, usize) {
+		self.fold_operands(&mut |node, children: Vec<(usize, usize)>| {
+			let (pl, nl) = children.first().copied().unwrap_or((0, 0));
+			let (pr, nr) = children.get(1).copied().unwrap_or((0, 0));
+			let all = pl.saturating_add(nl).saturating_add(pr).saturating_add(nr);
+			match node.boolean_type {
+				BoolType::True | BoolType::False => (1, 1),
+				BoolType::Variable => (1, 2),
+				BoolType::Negation => (nl, pl),
+				BoolType::Conjunction | BoolType::Disjunction => {
+					// sizes of the binary form: one node less than operands
+					children.iter().fold((children.len() - 1, children.len() - 1),
+						|(pos, neg), (p, n)| {
+							(pos.saturating_add(*p), neg.saturating_add(*n))
+						})
+				},
+				BoolType::MaterialCondition => {
+					(nl.saturating_add(pr).saturating_add(1),
+						pl.saturating_add(nr).saturating_add(1))
+				},
+				BoolType::LogicalEquivalence | BoolType::ExclusiveDisjunction => {
+					(all.saturating_add(3), all.saturating_add(3))
+				},
+				_ => panic!("unexpected op '{}'", node.boolean_type),
+			}
+		})
+	}
+
+	/// Conjunctive normal form, as a right-handed chain of conjunctions of
+	/// right-handed chains of disjunctions (see `to_cnf_nary`).
 	pub fn to_cnf(&mut self) {
+		self.to_cnf_nary();
+		self.to_binary();
+	}
+
+	pub fn to_dnf(&mut self) {
+		self.to_dnf_nary();
+		self.to_binary();
+	}
+
+	/// Same as `to_cnf` with n-ary nodes: a conjunction of clauses, each one
+	/// being a disjunction of literals (unless there is a single one), which
+	/// is what clause-level algorithms want. The formula may already have
+	/// n-ary nodes in it.
+	pub fn to_cnf_nary(&mut self) {
+		self.to_nnf();
+		self.nary_normal_form(BoolType::Disjunction, &Guard::new(Budget::default()))
+			.expect("conversion without budget");
+	}
+
+	pub fn to_dnf_nary(&mut self) {
 		self.to_nnf();
-		self.cnf();
+		self.nary_normal_form(BoolType::Conjunction, &Guard::new(Budget::default()))
+			.expect("conversion without budget");
+	}
+
+	fn bounded(&mut self, inner: BoolType, budget: &Budget) -> Result<(), BudgetExceeded> {
+		let guard = Guard::new(*budget);
+		let mut formula = self.clone();
+		formula.expand_within(&guard)?;
+		guard.check(formula.nnf_size().0, 1)?;
+		formula.to_nnf();
+		formula.nary_normal_form(inner, &guard)?;
+		formula.to_binary();
+		let clauses = match inner {
+			BoolType::Disjunction => formula.conjuncts().len(),
+			_ => formula.disjuncts().len(),
+		};
+		guard.check(formula.size(), clauses)?;
+		*self = formula;
+		Ok(())
+	}
+
+	/// Same as `to_cnf` but gives up as soon as the budget is exceeded, in
+	/// which case the formula is left untouched. Cardinality constraints and
+	/// quantifiers are expanded first, which is charged to the budget too: a
+	/// constraint is given up before its binomial expansion is built, and a
+	/// quantifier as soon as its expansion is too large.
+	pub fn to_cnf_bounded(&mut self, budget: &Budget) -> Result<(), BudgetExceeded> {
+		self.bounded(BoolType::Disjunction, budget)
+	}
+
+	pub fn to_dnf_bounded(&mut self, budget: &Budget) -> Result<(), BudgetExceeded> {
+		self.bounded(BoolType::Conjunction, budget)
+	}
+
+	/// Paths only go through left and right children, so n-ary nodes are
+	/// made binary before the conversion starts (not recorded as a step).
+	fn traced(&mut self, conversion: impl FnOnce(&mut Self, &mut Tracer)) -> Trace {
+		self.to_binary();
+		let initial = self.to_formula();
+		let mut tracer = Tracer::new(true);
+		conversion(self, &mut tracer);
+		Trace { initial, steps: tracer.steps.unwrap_or_default() }
+	}
+
+	/// Same as `to_nnf`, returning every rewrite which was done.
+	pub fn to_nnf_traced(&mut self) -> Trace {
+		self.traced(|node, tracer| node.nnf(tracer))
+	}
+
+	pub fn to_cnf_traced(&mut self) -> Trace {
+		self.traced(|node, tracer| {
+			node.nnf(tracer);
+			node.normal_form(BoolType::Disjunction, tracer);
+		})
+	}
+
+	pub fn to_dnf_traced(&mut self) -> Trace {
+		self.traced(|node, tracer| {
+			node.nnf(tracer);
+			node.normal_form(BoolType::Conjunction, tracer);
+		})
 	}
 }
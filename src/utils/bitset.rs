@@ -0,0 +1,459 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use super::sets::set_variables;
+
+const WORD_BITS: usize = u64::BITS as usize;
+/// Number of positions in a chunk of a compressed set.
+const CHUNK_BITS: usize = 1 << 16;
+const CHUNK_WORDS: usize = CHUNK_BITS / WORD_BITS;
+/// Chunks with more elements than this are kept as bitmaps, the others as
+/// sorted arrays (a bitmap takes as much memory as an array of this size).
+const ARRAY_LIMIT: usize = 4096;
+
+/// Set of positions in a universe of `size` elements (see `Index`), where
+/// every operation is done on whole words of 64 positions.
+pub trait BitSet: Clone + PartialEq {
+	fn empty(size: usize) -> Self;
+	fn insert(&mut self, position: usize);
+	fn contains(&self, position: usize) -> bool;
+	/// Number of elements of the universe.
+	fn size(&self) -> usize;
+	/// Number of elements of the set.
+	fn count(&self) -> usize;
+	/// Positions of the elements, in increasing order.
+	fn positions(&self) -> Vec<usize>;
+	/// Apply a bitwise operation on the words of both sets. The positions
+	/// which are in neither set are only looked at if `op(0, 0)` is not 0.
+	fn combine(&self, other: &Self, op: fn(u64, u64) -> u64) -> Self;
+
+	fn full(size: usize) -> Self {
+		Self::empty(size).complement()
+	}
+
+	fn is_empty(&self) -> bool {
+		self.count() == 0
+	}
+
+	fn complement(&self) -> Self {
+		self.combine(self, |a, _| !a)
+	}
+
+	fn intersection(&self, other: &Self) -> Self {
+		self.combine(other, |a, b| a & b)
+	}
+
+	fn union(&self, other: &Self) -> Self {
+		self.combine(other, |a, b| a | b)
+	}
+
+	fn symmetric_difference(&self, other: &Self) -> Self {
+		self.combine(other, |a, b| a ^ b)
+	}
+
+	fn difference(&self, other: &Self) -> Self {
+		self.combine(other, |a, b| a & !b)
+	}
+
+	fn implication(&self, other: &Self) -> Self {
+		self.combine(other, |a, b| !a | b)
+	}
+
+	/// Elements which are in both sets or in neither.
+	fn equivalence(&self, other: &Self) -> Self {
+		self.combine(other, |a, b| !(a ^ b))
+	}
+
+	fn is_subset(&self, other: &Self) -> bool {
+		self.difference(other).is_empty()
+	}
+
+	fn is_disjoint(&self, other: &Self) -> bool {
+		self.intersection(other).is_empty()
+	}
+}
+
+fn check_sizes(a: usize, b: usize) {
+	if a != b {
+		panic!("bitsets of universes of {} and {} elements", a, b);
+	}
+}
+
+fn check_position(position: usize, size: usize) {
+	if position >= size {
+		panic!("position {} is out of a universe of {} elements", position, size);
+	}
+}
+
+/// Clear the bits of the words which are not below `size`.
+fn mask_tail(words: &mut [u64], size: usize) {
+	let full_words = size / WORD_BITS;
+	if let Some(last) = words.get_mut(full_words) {
+		*last &= (1u64 << (size % WORD_BITS)) - 1;
+	}
+	for word in words.iter_mut().skip(full_words + 1) {
+		*word = 0;
+	}
+}
+
+fn word_positions(words: &[u64], offset: usize, positions: &mut Vec<usize>) {
+	for (index, word) in words.iter().enumerate() {
+		let mut word = *word;
+		while word != 0 {
+			positions.push(offset + index * WORD_BITS + word.trailing_zeros() as usize);
+			word &= word - 1;
+		}
+	}
+}
+
+/// Bitset with one bit for each element of the universe.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DenseSet {
+	words: Vec<u64>,
+	size: usize,
+}
+
+impl DenseSet {
+	pub fn words(&self) -> &[u64] {
+		&self.words
+	}
+}
+
+impl BitSet for DenseSet {
+	fn empty(size: usize) -> Self {
+		DenseSet { words: vec![0; size.div_ceil(WORD_BITS)], size }
+	}
+
+	fn insert(&mut self, position: usize) {
+		check_position(position, self.size);
+		self.words[position / WORD_BITS] |= 1 << (position % WORD_BITS);
+	}
+
+	fn contains(&self, position: usize) -> bool {
+		position < self.size
+			&& self.words[position / WORD_BITS] >> (position % WORD_BITS) & 1 != 0
+	}
+
+	fn size(&self) -> usize {
+		self.size
+	}
+
+	fn count(&self) -> usize {
+		self.words.iter().map(|word| word.count_ones() as usize).sum()
+	}
+
+	fn positions(&self) -> Vec<usize> {
+		let mut positions = Vec::with_capacity(self.count());
+		word_positions(&self.words, 0, &mut positions);
+		positions
+	}
+
+	fn combine(&self, other: &Self, op: fn(u64, u64) -> u64) -> Self {
+		check_sizes(self.size, other.size);
+		let mut words: Vec<u64> = self.words.iter().zip(other.words.iter())
+			.map(|(a, b)| op(*a, *b))
+			.collect();
+		mask_tail(&mut words, self.size);
+		DenseSet { words, size: self.size }
+	}
+}
+
+/// Elements of a chunk of a compressed set (their low 16 bits).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Container {
+	Array(Vec<u16>),
+	Bitmap(Vec<u64>),
+}
+
+impl Container {
+	fn from_array(lows: Vec<u16>) -> Option<Self> {
+		match lows.len() {
+			0 => None,
+			n if n <= ARRAY_LIMIT => Some(Container::Array(lows)),
+			_ => {
+				let mut words = vec![0; CHUNK_WORDS];
+				for low in lows {
+					words[low as usize / WORD_BITS] |= 1 << (low as usize % WORD_BITS);
+				}
+				Some(Container::Bitmap(words))
+			},
+		}
+	}
+
+	fn from_words(words: Vec<u64>) -> Option<Self> {
+		let count: usize = words.iter().map(|word| word.count_ones() as usize).sum();
+		match count {
+			0 => None,
+			n if n <= ARRAY_LIMIT => {
+				let mut positions = Vec::with_capacity(n);
+				word_positions(&words, 0, &mut positions);
+				Some(Container::Array(positions.into_iter().map(|p| p as u16).collect()))
+			},
+			_ => Some(Container::Bitmap(words)),
+		}
+	}
+
+	fn words(&self) -> Cow<'_, [u64]> {
+		match self {
+			Container::Array(lows) => {
+				let mut words = vec![0; CHUNK_WORDS];
+				for low in lows {
+					words[*low as usize / WORD_BITS] |= 1 << (*low as usize % WORD_BITS);
+				}
+				Cow::Owned(words)
+			},
+			Container::Bitmap(words) => Cow::Borrowed(words),
+		}
+	}
+
+	/// Words of a chunk which may be missing (so empty).
+	fn words_of(chunk: Option<&Self>) -> Cow<'_, [u64]> {
+		chunk.map_or(Cow::Owned(vec![0; CHUNK_WORDS]), Container::words)
+	}
+
+	fn contains(&self, low: u16) -> bool {
+		match self {
+			Container::Array(lows) => lows.binary_search(&low).is_ok(),
+			Container::Bitmap(words) => {
+				words[low as usize / WORD_BITS] >> (low as usize % WORD_BITS) & 1 != 0
+			},
+		}
+	}
+
+	fn count(&self) -> usize {
+		match self {
+			Container::Array(lows) => lows.len(),
+			Container::Bitmap(words) => {
+				words.iter().map(|word| word.count_ones() as usize).sum()
+			},
+		}
+	}
+}
+
+/// Compressed bitset in the manner of roaring bitmaps: the universe is cut
+/// in chunks of 2^16 positions, empty chunks are not stored, sparse ones are
+/// sorted arrays and dense ones are bitmaps. Two sets are equal if and only
+/// if they are made of the same chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompressedSet {
+	/// Chunks sorted by key (the high bits of their positions).
+	chunks: Vec<(usize, Container)>,
+	size: usize,
+}
+
+impl CompressedSet {
+	fn chunk(&self, key: usize) -> Option<&Container> {
+		self.chunks.binary_search_by_key(&key, |(k, _)| *k)
+			.ok()
+			.map(|index| &self.chunks[index].1)
+	}
+
+	/// Keys of the chunks the result of `op` may have elements in.
+	fn combined_keys(&self, other: &Self, op: fn(u64, u64) -> u64) -> Vec<usize> {
+		if op(0, 0) != 0 {
+			return (0..self.size.div_ceil(CHUNK_BITS)).collect();
+		}
+		let mut keys: Vec<usize> = self.chunks.iter().chain(other.chunks.iter())
+			.map(|(key, _)| *key)
+			.collect();
+		keys.sort_unstable();
+		keys.dedup();
+		keys
+	}
+
+	/// Number of stored chunks which are bitmaps (the others are arrays).
+	pub fn bitmap_chunks(&self) -> usize {
+		self.chunks.iter()
+			.filter(|(_, container)| matches!(container, Container::Bitmap(_)))
+			.count()
+	}
+
+	pub fn chunk_count(&self) -> usize {
+		self.chunks.len()
+	}
+}
+
+impl BitSet for CompressedSet {
+	fn empty(size: usize) -> Self {
+		CompressedSet { chunks: vec![], size }
+	}
+
+	fn insert(&mut self, position: usize) {
+		check_position(position, self.size);
+		let (key, low) = (position / CHUNK_BITS, (position % CHUNK_BITS) as u16);
+		match self.chunks.binary_search_by_key(&key, |(k, _)| *k) {
+			Err(index) => self.chunks.insert(index, (key, Container::Array(vec![low]))),
+			Ok(index) => match &mut self.chunks[index].1 {
+				Container::Array(lows) => {
+					if let Err(at) = lows.binary_search(&low) {
+						lows.insert(at, low);
+					}
+					if lows.len() > ARRAY_LIMIT {
+						let lows = std::mem::take(lows);
+						self.chunks[index].1 = Container::from_array(lows).unwrap();
+					}
+				},
+				Container::Bitmap(words) => {
+					words[low as usize / WORD_BITS] |= 1 << (low as usize % WORD_BITS);
+				},
+			},
+		}
+	}
+
+	fn contains(&self, position: usize) -> bool {
+		position < self.size
+			&& self.chunk(position / CHUNK_BITS)
+				.is_some_and(|chunk| chunk.contains((position % CHUNK_BITS) as u16))
+	}
+
+	fn size(&self) -> usize {
+		self.size
+	}
+
+	fn count(&self) -> usize {
+		self.chunks.iter().map(|(_, container)| container.count()).sum()
+	}
+
+	fn positions(&self) -> Vec<usize> {
+		let mut positions = Vec::with_capacity(self.count());
+		for (key, container) in self.chunks.iter() {
+			let offset = key * CHUNK_BITS;
+			match container {
+				Container::Array(lows) => {
+					positions.extend(lows.iter().map(|low| offset + *low as usize));
+				},
+				Container::Bitmap(words) => word_positions(words, offset, &mut positions),
+			}
+		}
+		positions
+	}
+
+	fn combine(&self, other: &Self, op: fn(u64, u64) -> u64) -> Self {
+		check_sizes(self.size, other.size);
+		let mut chunks = vec![];
+		for key in self.combined_keys(other, op) {
+			let (a, b) = (self.chunk(key), other.chunk(key));
+			let sparse = |chunk: Option<&Container>| {
+				!matches!(chunk, Some(Container::Bitmap(_)))
+			};
+			let container = if op(0, 0) & 1 == 0 && sparse(a) && sparse(b) {
+				// merge the arrays, op being given one bit words for each element
+				let lows = |chunk: Option<&Container>| match chunk {
+					Some(Container::Array(lows)) => lows.clone(),
+					_ => vec![],
+				};
+				let mut candidates = lows(a);
+				candidates.append(&mut lows(b));
+				candidates.sort_unstable();
+				candidates.dedup();
+				candidates.retain(|low| {
+					let bit = |chunk: Option<&Container>| {
+						chunk.map_or(0, |chunk| chunk.contains(*low) as u64)
+					};
+					op(bit(a), bit(b)) & 1 != 0
+				});
+				Container::from_array(candidates)
+			} else {
+				let (a, b) = (Container::words_of(a), Container::words_of(b));
+				let mut words: Vec<u64> = a.iter().zip(b.iter())
+					.map(|(a, b)| op(*a, *b))
+					.collect();
+				mask_tail(&mut words, self.size.saturating_sub(key * CHUNK_BITS));
+				Container::from_words(words)
+			};
+			if let Some(container) = container {
+				chunks.push((key, container));
+			}
+		}
+		CompressedSet { chunks, size: self.size }
+	}
+}
+
+/// Universe whose elements are numbered in order, so that its subsets can
+/// be represented by bitsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index<T> {
+	elements: Vec<T>,
+}
+
+impl<T: Ord + Clone> Index<T> {
+	pub fn new(mut elements: Vec<T>) -> Self {
+		elements.sort();
+		elements.dedup();
+		Index { elements }
+	}
+
+	pub fn len(&self) -> usize {
+		self.elements.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.elements.is_empty()
+	}
+
+	pub fn position(&self, element: &T) -> Option<usize> {
+		self.elements.binary_search(element).ok()
+	}
+
+	/// Bitset of the given elements, which must all be in the universe.
+	pub fn bitset<S: BitSet>(&self, elements: &[T]) -> S {
+		let mut set = S::empty(self.len());
+		for element in elements {
+			match self.position(element) {
+				Some(position) => set.insert(position),
+				None => panic!("set element is not in the universe"),
+			}
+		}
+		set
+	}
+
+	/// Elements of a bitset of this universe, in order.
+	pub fn elements<S: BitSet>(&self, set: &S) -> Vec<T> {
+		check_sizes(set.size(), self.len());
+		set.positions().into_iter()
+			.map(|position| self.elements[position].clone())
+			.collect()
+	}
+
+	/// Same as `sets::eval_set_in` with this universe, on bitsets of type
+	/// `S` (`DenseSet` or `CompressedSet`).
+	pub fn eval_set<S: BitSet>(&self, formula: &str, sets: &[Vec<T>]) -> Vec<T> {
+		let bitsets: Vec<S> = sets.iter().map(|set| self.bitset(set)).collect();
+		self.elements(&eval_bitset(formula, &bitsets))
+	}
+}
+
+/// Evaluate a set formula on bitsets of the same universe, which are given
+/// to the variables in order like in `sets::eval_set`.
+pub fn eval_bitset<S: BitSet>(formula: &str, sets: &[S]) -> S {
+	let mut stack: Vec<S> = Vec::new();
+
+	if formula.is_empty() {
+		panic!("formula string is empty");
+	}
+	let varmap: HashMap<char, &S> = set_variables(formula, sets.len())
+		.into_iter()
+		.zip(sets.iter())
+		.collect();
+	for op in formula.chars() {
+		let right = if op == '!' || op.is_ascii_uppercase() {
+			None
+		} else {
+			stack.pop()
+		};
+		let left = if op.is_ascii_uppercase() { None } else { stack.pop() };
+		match (op, left, right) {
+			('A'..='Z', None, None) => stack.push(varmap[&op].clone()),
+			('!', Some(a), None) => stack.push(a.complement()),
+			('&', Some(a), Some(b)) => stack.push(a.intersection(&b)),
+			('|', Some(a), Some(b)) => stack.push(a.union(&b)),
+			('^', Some(a), Some(b)) => stack.push(a.symmetric_difference(&b)),
+			('>', Some(a), Some(b)) => stack.push(a.implication(&b)),
+			('=', Some(a), Some(b)) => stack.push(a.equivalence(&b)),
+			_ => panic!("'{}' is not a valid op or is missing an argument", op),
+		}
+	}
+	if stack.len() > 1 {
+		panic!("the stack should be empty at the end");
+	}
+	stack.pop().expect("nothing to return (stack is empty)")
+}
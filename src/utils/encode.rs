@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+use super::ast::{BoolNode, BoolType, Budget, BudgetExceeded, AUXILIARY_VARIABLES};
+use super::sat::{self, Clause, Literal};
+
+/// CNF encodings of the cardinality constraints. Each one introduces
+/// auxiliary variables, so the resulting clauses are equisatisfiable with the
+/// constraint (not equivalent to it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+	/// Sinz's sequential counter, O(n·k) clauses (at-most constraints).
+	SequentialCounter,
+	/// Binary tree of unary counters, O(n²) clauses in the worst case.
+	Totalizer,
+	/// Batcher's odd-even merge sorting network, O(n·log²(n)) comparators.
+	SortingNetwork,
+}
+
+/// Allocator for auxiliary variables.
+#[derive(Debug, Clone)]
+pub struct Fresh {
+	next: u32,
+}
+
+impl Fresh {
+	/// Allocate variables which are not already used in the formula.
+	pub fn for_formula(formula: &BoolNode) -> Self {
+		let next = formula.variables().into_iter()
+			.filter(|var| AUXILIARY_VARIABLES.contains(var))
+			.map(|var| var as u32 + 1)
+			.max()
+			.unwrap_or(*AUXILIARY_VARIABLES.start() as u32);
+		Self { next }
+	}
+
+	pub fn variable(&mut self) -> char {
+		let variable = match std::char::from_u32(self.next) {
+			Some(c) if AUXILIARY_VARIABLES.contains(&c) => c,
+			_ => panic!("no auxiliary variable left"),
+		};
+		self.next += 1;
+		variable
+	}
+
+	pub fn literal(&mut self) -> Literal {
+		Literal::new(self.variable(), true)
+	}
+}
+
+/// Literal standing for the operand. Anything else than a literal is given
+/// an auxiliary variable defined as equivalent to it.
+fn operand_literal(
+	operand: &BoolNode,
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) -> Literal {
+	match (operand.boolean_type(), operand.left()) {
+		(BoolType::Variable, _) => Literal::new(operand.op_symbol(), true),
+		(BoolType::Negation, Some(child))
+			if child.boolean_type() == BoolType::Variable => {
+			Literal::new(child.op_symbol(), false)
+		},
+		_ => {
+			let literal = fresh.literal();
+			let definition = BoolNode::binary('=', literal.to_node(), operand.clone());
+			clauses.append(&mut sat::clauses(&definition));
+			literal
+		},
+	}
+}
+
+fn negated(literals: &[Literal]) -> Vec<Literal> {
+	literals.iter().map(|literal| literal.negated()).collect()
+}
+
+fn sequential_counter(
+	literals: &[Literal],
+	bound: usize,
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) {
+	let n = literals.len();
+	if bound >= n { return }
+	if bound == 0 {
+		for literal in literals { clauses.push(vec![literal.negated()]) }
+		return
+	}
+	// counter[i][j] is true if at least j + 1 of the first i + 1 are true
+	let counter: Vec<Vec<Literal>> = (0..n - 1)
+		.map(|_| (0..bound).map(|_| fresh.literal()).collect())
+		.collect();
+	clauses.push(vec![literals[0].negated(), counter[0][0]]);
+	for literal in &counter[0][1..] {
+		clauses.push(vec![literal.negated()]);
+	}
+	for i in 1..n - 1 {
+		clauses.push(vec![literals[i].negated(), counter[i][0]]);
+		clauses.push(vec![counter[i - 1][0].negated(), counter[i][0]]);
+		for j in 1..bound {
+			clauses.push(vec![literals[i].negated(),
+				counter[i - 1][j - 1].negated(), counter[i][j]]);
+			clauses.push(vec![counter[i - 1][j].negated(), counter[i][j]]);
+		}
+		clauses.push(vec![literals[i].negated(),
+			counter[i - 1][bound - 1].negated()]);
+	}
+	clauses.push(vec![literals[n - 1].negated(),
+		counter[n - 2][bound - 1].negated()]);
+}
+
+/// Unary count of the literals: the i-th output is true if and only if at
+/// least i + 1 literals are true.
+fn totalizer(
+	literals: &[Literal],
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) -> Vec<Literal> {
+	if literals.len() <= 1 { return literals.to_vec() }
+	let middle = literals.len() / 2;
+	let a = totalizer(&literals[..middle], fresh, clauses);
+	let b = totalizer(&literals[middle..], fresh, clauses);
+	let outputs: Vec<Literal> = (0..literals.len()).map(|_| fresh.literal()).collect();
+	for i in 0..=a.len() {
+		for j in 0..=b.len() {
+			if i + j > 0 {
+				let mut clause = vec![outputs[i + j - 1]];
+				if i > 0 { clause.push(a[i - 1].negated()) }
+				if j > 0 { clause.push(b[j - 1].negated()) }
+				clauses.push(clause);
+			}
+			if i + j < outputs.len() {
+				let mut clause = vec![outputs[i + j].negated()];
+				if i < a.len() { clause.push(a[i]) }
+				if j < b.len() { clause.push(b[j]) }
+				clauses.push(clause);
+			}
+		}
+	}
+	outputs
+}
+
+fn comparator(
+	a: Literal,
+	b: Literal,
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) -> (Literal, Literal) {
+	let (max, min) = (fresh.literal(), fresh.literal());
+	clauses.push(vec![a.negated(), max]);
+	clauses.push(vec![b.negated(), max]);
+	clauses.push(vec![a, b, max.negated()]);
+	clauses.push(vec![a.negated(), b.negated(), min]);
+	clauses.push(vec![a, min.negated()]);
+	clauses.push(vec![b, min.negated()]);
+	(max, min)
+}
+
+fn odd_even_merge(
+	a: &[Literal],
+	b: &[Literal],
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) -> Vec<Literal> {
+	if a.len() == 1 {
+		let (max, min) = comparator(a[0], b[0], fresh, clauses);
+		return vec![max, min];
+	}
+	let evens = |v: &[Literal]| v.iter().step_by(2).copied().collect::<Vec<_>>();
+	let odds = |v: &[Literal]| v.iter().skip(1).step_by(2).copied().collect::<Vec<_>>();
+	let v = odd_even_merge(&evens(a), &evens(b), fresh, clauses);
+	let w = odd_even_merge(&odds(a), &odds(b), fresh, clauses);
+	let mut result = vec![v[0]];
+	for i in 0..v.len() - 1 {
+		let (max, min) = comparator(w[i], v[i + 1], fresh, clauses);
+		result.push(max);
+		result.push(min);
+	}
+	result.push(w[w.len() - 1]);
+	result
+}
+
+fn odd_even_sort(
+	literals: &[Literal],
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) -> Vec<Literal> {
+	if literals.len() <= 1 { return literals.to_vec() }
+	let middle = literals.len() / 2;
+	let a = odd_even_sort(&literals[..middle], fresh, clauses);
+	let b = odd_even_sort(&literals[middle..], fresh, clauses);
+	odd_even_merge(&a, &b, fresh, clauses)
+}
+
+/// Sorted copy of the literals, true ones first.
+fn sorting_network(
+	literals: &[Literal],
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) -> Vec<Literal> {
+	let mut padded = literals.to_vec();
+	while !padded.len().is_power_of_two() {
+		let padding = fresh.literal();
+		clauses.push(vec![padding.negated()]);
+		padded.push(padding);
+	}
+	odd_even_sort(&padded, fresh, clauses)
+}
+
+fn encode_at_most(
+	literals: &[Literal],
+	bound: usize,
+	encoding: Encoding,
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) {
+	if bound >= literals.len() { return }
+	let outputs = match encoding {
+		Encoding::SequentialCounter => {
+			return sequential_counter(literals, bound, fresh, clauses);
+		},
+		Encoding::Totalizer => totalizer(literals, fresh, clauses),
+		Encoding::SortingNetwork => sorting_network(literals, fresh, clauses),
+	};
+	clauses.push(vec![outputs[bound].negated()]);
+}
+
+/// Clauses enforcing a cardinality constraint over the given literals.
+pub fn encode_cardinality(
+	boolean_type: BoolType,
+	bound: usize,
+	literals: &[Literal],
+	encoding: Encoding,
+	fresh: &mut Fresh,
+) -> Vec<Clause> {
+	let mut clauses: Vec<Clause> = vec![];
+	let n = literals.len();
+
+	if boolean_type != BoolType::AtMost && bound > n {
+		return vec![vec![]];
+	}
+	if boolean_type != BoolType::AtLeast {
+		encode_at_most(literals, bound, encoding, fresh, &mut clauses);
+	}
+	if boolean_type != BoolType::AtMost {
+		// at least k of the literals is at most n - k of their negations
+		encode_at_most(&negated(literals), n - bound, encoding, fresh, &mut clauses);
+	}
+	clauses
+}
+
+fn encode_conjunct(
+	conjunct: &BoolNode,
+	encoding: Encoding,
+	fresh: &mut Fresh,
+	clauses: &mut Vec<Clause>,
+) {
+	match conjunct.boolean_type() {
+		BoolType::AtMost | BoolType::AtLeast | BoolType::Exactly => {
+			let literals: Vec<Literal> = conjunct.operands().iter()
+				.map(|operand| operand_literal(operand, fresh, clauses))
+				.collect();
+			clauses.append(&mut encode_cardinality(conjunct.boolean_type(),
+				conjunct.bound(), &literals, encoding, fresh));
+		},
+		_ => clauses.append(&mut sat::clauses(conjunct)),
+	}
+}
+
+/// Clauses of the formula where each top-level cardinality constraint is
+/// encoded with the given encoding. The other conjuncts, and the cardinality
+/// constraints nested in them, go through `to_cnf`.
+pub fn clauses(formula: &BoolNode, encoding: Encoding) -> Vec<Clause> {
+	let mut fresh = Fresh::for_formula(formula);
+	let mut clauses: Vec<Clause> = vec![];
+
+	for conjunct in formula.conjuncts() {
+		encode_conjunct(conjunct, encoding, &mut fresh, &mut clauses);
+	}
+	clauses
+}
+
+/// Same as `clauses` as a CNF formula.
+pub fn to_cnf(formula: &BoolNode, encoding: Encoding) -> BoolNode {
+	sat::clauses_to_node(&clauses(formula, encoding))
+}
+
+pub fn satisfiable(formula: &BoolNode, encoding: Encoding) -> bool {
+	sat::solve(&clauses(formula, encoding)).is_some()
+}
+
+/// Literal equivalent to the subformula, each operation being given an
+/// auxiliary variable defined by its clauses.
+fn tseitin_literal(node: &BoolNode, fresh: &mut Fresh, clauses: &mut Vec<Clause>) -> Literal {
+	match (node.boolean_type(), node.left(), node.right()) {
+		(BoolType::Variable, _, _) => Literal::new(node.op_symbol(), true),
+		(BoolType::Negation, Some(child), None) => {
+			tseitin_literal(child, fresh, clauses).negated()
+		},
+		(BoolType::True | BoolType::False, _, _) => {
+			let x = fresh.literal();
+			let value = node.boolean_type() == BoolType::True;
+			clauses.push(vec![if value { x } else { x.negated() }]);
+			x
+		},
+		(op @ (BoolType::Conjunction | BoolType::Disjunction), None, None)
+			if node.is_nary() => {
+			let literals: Vec<Literal> = node.operands().iter()
+				.map(|operand| tseitin_literal(operand, fresh, clauses))
+				.collect();
+			let x = fresh.literal();
+			// x ⇒ ∧ literals and ∧ literals ⇒ x (the dual for disjunctions)
+			let (x, literals) = if op == BoolType::Conjunction {
+				(x, literals)
+			} else {
+				(x.negated(), negated(&literals))
+			};
+			for literal in literals.iter() {
+				clauses.push(vec![x.negated(), *literal]);
+			}
+			let mut definition = vec![x];
+			definition.append(&mut negated(&literals));
+			clauses.push(definition);
+			if op == BoolType::Conjunction { x } else { x.negated() }
+		},
+		(op, Some(left), Some(right)) => {
+			let a = tseitin_literal(left, fresh, clauses);
+			let b = tseitin_literal(right, fresh, clauses);
+			let x = fresh.literal();
+			let (na, nb, nx) = (a.negated(), b.negated(), x.negated());
+			let mut definition = match op {
+				BoolType::Conjunction => vec![vec![nx, a], vec![nx, b], vec![x, na, nb]],
+				BoolType::Disjunction => vec![vec![nx, a, b], vec![x, na], vec![x, nb]],
+				BoolType::MaterialCondition => {
+					vec![vec![nx, na, b], vec![x, a], vec![x, nb]]
+				},
+				BoolType::LogicalEquivalence => vec![vec![nx, na, b], vec![nx, a, nb],
+					vec![x, a, b], vec![x, na, nb]],
+				BoolType::ExclusiveDisjunction => vec![vec![nx, a, b], vec![nx, na, nb],
+					vec![x, na, b], vec![x, a, nb]],
+				_ => panic!("unexpected op '{}'", op),
+			};
+			clauses.append(&mut definition);
+			x
+		},
+		_ => panic!("invalid '{}' op", node.boolean_type()),
+	}
+}
+
+/// Tseitin encoding of the formula: a linear number of clauses which are
+/// equisatisfiable with it. Cardinality constraints and quantifiers are
+/// expanded first.
+pub fn tseitin(formula: &BoolNode, fresh: &mut Fresh) -> Vec<Clause> {
+	let mut formula = formula.clone();
+	let mut clauses: Vec<Clause> = vec![];
+
+	formula.expand_cardinality();
+	formula.eliminate_quantifiers();
+	let root = tseitin_literal(&formula, fresh, &mut clauses);
+	clauses.push(vec![root]);
+	clauses
+}
+
+/// Result of `bounded_cnf`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoundedCnf {
+	/// The CNF of the formula.
+	Equivalent(BoolNode),
+	/// Its Tseitin encoding, given when the CNF does not fit in the budget.
+	Equisatisfiable(BoolNode),
+}
+
+impl BoundedCnf {
+	pub fn formula(&self) -> &BoolNode {
+		match self {
+			BoundedCnf::Equivalent(formula) | BoundedCnf::Equisatisfiable(formula) => formula,
+		}
+	}
+}
+
+/// CNF of the formula within the budget, falling back to the Tseitin
+/// encoding when it is exceeded. The encoding must fit in the node and
+/// clause limits too (the time limit only applies to the CNF conversion),
+/// and so must the expansion of its cardinality constraints and quantifiers.
+pub fn bounded_cnf(formula: &BoolNode, budget: &Budget) -> Result<BoundedCnf, BudgetExceeded> {
+	let mut cnf = formula.clone();
+	if cnf.to_cnf_bounded(budget).is_ok() {
+		return Ok(BoundedCnf::Equivalent(cnf));
+	}
+	let budget = Budget { max_time: None, ..*budget };
+	let mut expanded = formula.clone();
+	expanded.expand_bounded(&budget)?;
+	let mut fresh = Fresh::for_formula(&expanded);
+	let mut encoding = sat::clauses_to_node(&tseitin(&expanded, &mut fresh));
+	encoding.to_cnf_bounded(&budget)?;
+	Ok(BoundedCnf::Equisatisfiable(encoding))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+	AtMost,
+	AtLeast,
+	Equal,
+}
+
+/// Linear pseudo-Boolean constraint: Σ wᵢ·xᵢ compared to a bound, each xᵢ
+/// counting for 1 when true.
+#[derive(Debug, Clone)]
+pub struct PseudoBoolean {
+	pub terms: Vec<(u64, BoolNode)>,
+	pub relation: Relation,
+	pub bound: u64,
+}
+
+/// Decision diagram of Σ wᵢ·xᵢ ≤ k, with one auxiliary variable by
+/// (term, remaining bound) node.
+struct Diagram<'a> {
+	weights: &'a [u64],
+	literals: &'a [Literal],
+	suffix_sums: Vec<u64>,
+	nodes: HashMap<(usize, u64), Literal>,
+}
+
+impl<'a> Diagram<'a> {
+	fn new(weights: &'a [u64], literals: &'a [Literal]) -> Self {
+		let mut suffix_sums = vec![0; weights.len() + 1];
+		for index in (0..weights.len()).rev() {
+			suffix_sums[index] = suffix_sums[index + 1] + weights[index];
+		}
+		Self { weights, literals, suffix_sums, nodes: HashMap::new() }
+	}
+
+	/// Either the literal of the node or its value if it is a terminal.
+	fn node(
+		&mut self,
+		index: usize,
+		bound: u64,
+		fresh: &mut Fresh,
+		clauses: &mut Vec<Clause>,
+	) -> Result<Literal, bool> {
+		if self.suffix_sums[index] <= bound { return Err(true) }
+		if let Some(literal) = self.nodes.get(&(index, bound)) {
+			return Ok(*literal);
+		}
+		let x = self.literals[index];
+		let high = match bound.checked_sub(self.weights[index]) {
+			Some(rest) => self.node(index + 1, rest, fresh, clauses),
+			None => Err(false),
+		};
+		let low = self.node(index + 1, bound, fresh, clauses);
+		let node = fresh.literal();
+		// node ⇔ (x ∧ high) ∨ (¬x ∧ low)
+		for (branch, condition) in [(high, x), (low, x.negated())].iter() {
+			match branch {
+				Ok(literal) => {
+					clauses.push(vec![node.negated(), condition.negated(), *literal]);
+					clauses.push(vec![node, condition.negated(), literal.negated()]);
+				},
+				Err(false) => {
+					clauses.push(vec![node.negated(), condition.negated()]);
+				},
+				Err(true) => {
+					clauses.push(vec![node, condition.negated()]);
+				},
+			}
+		}
+		self.nodes.insert((index, bound), node);
+		Ok(node)
+	}
+}
+
+impl PseudoBoolean {
+	pub fn new(terms: Vec<(u64, BoolNode)>, relation: Relation, bound: u64) -> Self {
+		Self { terms, relation, bound }
+	}
+
+	fn total(&self) -> u64 {
+		self.terms.iter().map(|(weight, _)| weight).sum()
+	}
+
+	/// Σ wᵢ·xᵢ ≥ k is Σ wᵢ·¬xᵢ ≤ Σ wᵢ - k, so every relation comes down to
+	/// one or two "at most" constraints, given as (negated terms, bound).
+	fn at_most_constraints(&self) -> Option<Vec<(bool, u64)>> {
+		let total = self.total();
+		let reversed = total.checked_sub(self.bound);
+		match (self.relation, reversed) {
+			(Relation::AtMost, _) => Some(vec![(false, self.bound)]),
+			(_, None) => None,
+			(Relation::AtLeast, Some(reversed)) => Some(vec![(true, reversed)]),
+			(Relation::Equal, Some(reversed)) => {
+				Some(vec![(false, self.bound), (true, reversed)])
+			},
+		}
+	}
+
+	fn at_most_node(operands: &[(u64, BoolNode)], bound: u64) -> BoolNode {
+		let total: u64 = operands.iter().map(|(weight, _)| weight).sum();
+		if total <= bound { return BoolNode::constant(true) }
+		let (weight, x) = &operands[0];
+		let high = match bound.checked_sub(*weight) {
+			Some(rest) => Self::at_most_node(&operands[1..], rest),
+			None => BoolNode::constant(false),
+		};
+		let low = Self::at_most_node(&operands[1..], bound);
+		let high = BoolNode::binary('&', x.clone(), high).simplify();
+		let low = BoolNode::binary('&', BoolNode::unary('!', x.clone()), low).simplify();
+		BoolNode::binary('|', high, low).simplify()
+	}
+
+	/// Equivalent formula (a decision tree over the terms, which can be
+	/// exponentially big).
+	pub fn to_node(&self) -> BoolNode {
+		let constraints = match self.at_most_constraints() {
+			Some(constraints) => constraints,
+			None => return BoolNode::constant(false),
+		};
+		let nodes = constraints.into_iter().map(|(negate, bound)| {
+			let operands: Vec<(u64, BoolNode)> = self.terms.iter()
+				.map(|(weight, x)| {
+					let x = if negate { BoolNode::unary('!', x.clone()) } else { x.clone() };
+					(*weight, x)
+				})
+				.collect();
+			Self::at_most_node(&operands, bound)
+		}).collect();
+		BoolNode::conjunction(nodes).simplify()
+	}
+
+	/// Equisatisfiable clauses, built from a decision diagram whose nodes
+	/// are shared, so the size is bounded by the number of terms times the
+	/// number of distinct partial sums.
+	pub fn encode(&self, fresh: &mut Fresh) -> Vec<Clause> {
+		let mut clauses: Vec<Clause> = vec![];
+		let constraints = match self.at_most_constraints() {
+			Some(constraints) => constraints,
+			None => return vec![vec![]],
+		};
+		let weights: Vec<u64> = self.terms.iter().map(|(weight, _)| *weight).collect();
+		let literals: Vec<Literal> = self.terms.iter()
+			.map(|(_, x)| operand_literal(x, fresh, &mut clauses))
+			.collect();
+		for (negate, bound) in constraints {
+			let literals = if negate { negated(&literals) } else { literals.clone() };
+			let mut diagram = Diagram::new(&weights, &literals);
+			match diagram.node(0, bound, fresh, &mut clauses) {
+				Ok(root) => clauses.push(vec![root]),
+				Err(true) => (),
+				Err(false) => clauses.push(vec![]),
+			}
+		}
+		clauses
+	}
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+
+/// Truth values of a many-valued logic and the operations of the formulas on
+/// them. The equivalence is the conjunction of both implications and the
+/// exclusive disjunction is its negation, like in the boolean logic.
+pub trait Logic {
+	type Value: Copy + fmt::Debug;
+
+	/// Value of the '0' and '1' constants.
+	fn constant(&self, value: bool) -> Self::Value;
+	fn negation(&self, a: Self::Value) -> Self::Value;
+	fn conjunction(&self, a: Self::Value, b: Self::Value) -> Self::Value;
+	fn disjunction(&self, a: Self::Value, b: Self::Value) -> Self::Value;
+	fn implication(&self, a: Self::Value, b: Self::Value) -> Self::Value;
+
+	fn equivalence(&self, a: Self::Value, b: Self::Value) -> Self::Value {
+		self.conjunction(self.implication(a, b), self.implication(b, a))
+	}
+
+	fn exclusive_disjunction(&self, a: Self::Value, b: Self::Value) -> Self::Value {
+		self.negation(self.equivalence(a, b))
+	}
+
+	/// Values the operations are defined for.
+	fn is_valid(&self, _value: Self::Value) -> bool {
+		true
+	}
+}
+
+/// Truth value of the Kleene logic, ordered from false to true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Trilean {
+	False,
+	Unknown,
+	True,
+}
+
+impl From<bool> for Trilean {
+	fn from(value: bool) -> Self {
+		if value { Trilean::True } else { Trilean::False }
+	}
+}
+
+impl fmt::Display for Trilean {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let symbol = match self {
+			Trilean::False => '0',
+			Trilean::Unknown => '?',
+			Trilean::True => '1',
+		};
+		write!(f, "{}", symbol)
+	}
+}
+
+/// Strong Kleene logic: a value is unknown if it depends on an unknown
+/// operand, so the conjunction is the minimum and the disjunction the
+/// maximum of the operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kleene;
+
+impl Logic for Kleene {
+	type Value = Trilean;
+
+	fn constant(&self, value: bool) -> Trilean {
+		Trilean::from(value)
+	}
+
+	fn negation(&self, a: Trilean) -> Trilean {
+		match a {
+			Trilean::False => Trilean::True,
+			Trilean::Unknown => Trilean::Unknown,
+			Trilean::True => Trilean::False,
+		}
+	}
+
+	fn conjunction(&self, a: Trilean, b: Trilean) -> Trilean {
+		a.min(b)
+	}
+
+	fn disjunction(&self, a: Trilean, b: Trilean) -> Trilean {
+		a.max(b)
+	}
+
+	fn implication(&self, a: Trilean, b: Trilean) -> Trilean {
+		self.disjunction(self.negation(a), b)
+	}
+}
+
+/// Fuzzy logic over the truth degrees in [0, 1], the conjunction being the
+/// t-norm, the disjunction its dual t-conorm, the implication its residuum
+/// and the negation 1 - a.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TNorm {
+	/// Gödel logic.
+	Minimum,
+	/// Goguen logic.
+	Product,
+	Lukasiewicz,
+}
+
+impl Logic for TNorm {
+	type Value = f64;
+
+	fn constant(&self, value: bool) -> f64 {
+		if value { 1.0 } else { 0.0 }
+	}
+
+	fn negation(&self, a: f64) -> f64 {
+		1.0 - a
+	}
+
+	fn conjunction(&self, a: f64, b: f64) -> f64 {
+		match self {
+			TNorm::Minimum => a.min(b),
+			TNorm::Product => a * b,
+			TNorm::Lukasiewicz => (a + b - 1.0).max(0.0),
+		}
+	}
+
+	fn disjunction(&self, a: f64, b: f64) -> f64 {
+		match self {
+			TNorm::Minimum => a.max(b),
+			TNorm::Product => a + b - a * b,
+			TNorm::Lukasiewicz => (a + b).min(1.0),
+		}
+	}
+
+	fn implication(&self, a: f64, b: f64) -> f64 {
+		match self {
+			_ if a <= b => 1.0,
+			TNorm::Minimum => b,
+			TNorm::Product => b / a,
+			TNorm::Lukasiewicz => 1.0 - a + b,
+		}
+	}
+
+	fn is_valid(&self, value: f64) -> bool {
+		(0.0..=1.0).contains(&value)
+	}
+}
+
+/// Operation of a formula checked by `compile`, the variables being replaced
+/// by the index of their value.
+enum Op {
+	Constant(bool),
+	Variable(usize),
+	Operator(char),
+}
+
+/// Check a formula with the operators of `eval_formula` and as many
+/// variables as there are values, so that it can be evaluated on many values
+/// without being parsed again.
+fn compile(formula: &str, value_count: usize) -> Vec<Op> {
+	let mut program: Vec<Op> = Vec::with_capacity(formula.len());
+	let mut stack_size = 0;
+
+	if formula.is_empty() {
+		panic!("formula string is empty");
+	}
+	let mut variables: Vec<char> = formula.chars().filter(char::is_ascii_uppercase).collect();
+	variables.sort_unstable();
+	variables.dedup();
+	if variables.len() > value_count {
+		panic!("missing values for '{}' formula", formula);
+	} else if value_count > variables.len() {
+		panic!("too many values for '{}' formula", formula);
+	}
+	for op in formula.chars() {
+		let operands = match op {
+			'0' | '1' | 'A'..='Z' => 0,
+			'!' => 1,
+			'&' | '|' | '^' | '>' | '=' => 2,
+			_ => panic!("'{}' is not a valid op or is missing an argument", op),
+		};
+		if stack_size < operands {
+			panic!("'{}' is not a valid op or is missing an argument", op);
+		}
+		stack_size = stack_size - operands + 1;
+		program.push(match op {
+			'0' | '1' => Op::Constant(op == '1'),
+			'A'..='Z' => Op::Variable(variables.binary_search(&op).unwrap()),
+			_ => Op::Operator(op),
+		});
+	}
+	if stack_size > 1 {
+		panic!("the stack should be empty at the end");
+	}
+	program
+}
+
+/// Evaluate a formula checked by `compile` on its values.
+fn run<L: Logic>(logic: &L, program: &[Op], values: &[L::Value]) -> L::Value {
+	let mut stack: Vec<L::Value> = Vec::new();
+
+	for op in program {
+		let value = match op {
+			Op::Constant(value) => logic.constant(*value),
+			Op::Variable(index) => values[*index],
+			Op::Operator('!') => {
+				let a = stack.pop().unwrap();
+				logic.negation(a)
+			},
+			Op::Operator(op) => {
+				let b = stack.pop().unwrap();
+				let a = stack.pop().unwrap();
+				match op {
+					'&' => logic.conjunction(a, b),
+					'|' => logic.disjunction(a, b),
+					'^' => logic.exclusive_disjunction(a, b),
+					'>' => logic.implication(a, b),
+					_ => logic.equivalence(a, b),
+				}
+			},
+		};
+		stack.push(value);
+	}
+	stack.pop().expect("nothing to return (stack is empty)")
+}
+
+/// Evaluate a formula in a many-valued logic, with the operators of
+/// `eval_formula`. The formula may have variables, which are given their
+/// value in alphabetical order (the first value is given to the first
+/// variable and so on).
+pub fn eval_many_valued<L: Logic>(logic: &L, formula: &str, values: &[L::Value]) -> L::Value {
+	let program = compile(formula, values.len());
+	if let Some(value) = values.iter().find(|value| !logic.is_valid(**value)) {
+		panic!("{:?} is not a valid truth value", value);
+	}
+	run(logic, &program, values)
+}
+
+/// Evaluate a set formula on fuzzy sets, each element of which has a degree
+/// of membership (with the same variable order as `eval_set`). The degree of
+/// an element in the result is the value of the formula for its degrees in
+/// each set, 0 being the degree of the elements a set does not list and the
+/// highest one being kept when a set lists an element more than once. Like
+/// with `eval_set`, the formula is evaluated on the elements of every set,
+/// and the result only lists the elements with a degree above 0.
+pub fn eval_fuzzy_set<T: Ord + Clone>(
+	formula: &str,
+	sets: &[Vec<(T, f64)>],
+	t_norm: TNorm,
+) -> Vec<(T, f64)> {
+	let program = compile(formula, sets.len());
+	let sets: Vec<Vec<(T, f64)>> = sets.iter()
+		.map(|set| {
+			if let Some((_, degree)) = set.iter().find(|(_, degree)| !t_norm.is_valid(*degree)) {
+				panic!("{:?} is not a valid truth value", degree);
+			}
+			let mut set = set.clone();
+			set.sort_by(|(a, _), (b, _)| a.cmp(b));
+			set.dedup_by(|(element, degree), (kept, kept_degree)| {
+				if element != kept {
+					return false;
+				}
+				*kept_degree = kept_degree.max(*degree);
+				true
+			});
+			set
+		})
+		.collect();
+	let mut u: Vec<T> = sets.iter().flatten().map(|(element, _)| element.clone()).collect();
+	u.sort();
+	u.dedup();
+	u.into_iter()
+		.map(|element| {
+			let degrees: Vec<f64> = sets.iter().map(|set| {
+				set.binary_search_by(|(member, _)| member.cmp(&element))
+					.map_or(0.0, |index| set[index].1)
+			}).collect();
+			let degree = run(&t_norm, &program, &degrees);
+			(element, degree)
+		})
+		.filter(|(_, degree)| *degree > 0.0)
+		.collect()
+}
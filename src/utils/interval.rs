@@ -0,0 +1,233 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use super::ast::BoolNode;
+use super::sets::{set_variables, check_set_formula};
+
+/// Integer type an interval set can be made of.
+pub trait Discrete: Ord + Copy + fmt::Debug {
+	fn successor(self) -> Option<Self>;
+	fn predecessor(self) -> Option<Self>;
+	/// Number of integers from `start` to `end`, both included.
+	fn count(start: Self, end: Self) -> u128;
+}
+
+macro_rules! impl_discrete {
+	($($t:ty),*) => {$(
+		impl Discrete for $t {
+			fn successor(self) -> Option<Self> {
+				self.checked_add(1)
+			}
+
+			fn predecessor(self) -> Option<Self> {
+				self.checked_sub(1)
+			}
+
+			fn count(start: Self, end: Self) -> u128 {
+				(end as i128 - start as i128 + 1) as u128
+			}
+		}
+	)*};
+}
+
+impl_discrete!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Set of integers stored as sorted ranges, which are disjoint and not
+/// adjacent (so two equal sets have the same ranges).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+	ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T: Discrete> Default for IntervalSet<T> {
+	fn default() -> Self {
+		IntervalSet::new()
+	}
+}
+
+impl<T: Discrete> From<RangeInclusive<T>> for IntervalSet<T> {
+	fn from(range: RangeInclusive<T>) -> Self {
+		IntervalSet::from_ranges(vec![range])
+	}
+}
+
+impl<T: Discrete> From<Vec<T>> for IntervalSet<T> {
+	fn from(points: Vec<T>) -> Self {
+		IntervalSet::from_ranges(points.into_iter().map(|point| point..=point))
+	}
+}
+
+impl<T: Discrete> IntervalSet<T> {
+	pub fn new() -> Self {
+		IntervalSet { ranges: vec![] }
+	}
+
+	/// Set of the elements of every range, which may overlap or be empty.
+	pub fn from_ranges(ranges: impl IntoIterator<Item = RangeInclusive<T>>) -> Self {
+		let mut ranges: Vec<RangeInclusive<T>> = ranges.into_iter()
+			.filter(|range| !range.is_empty())
+			.collect();
+		ranges.sort_by_key(|range| *range.start());
+		let mut merged: Vec<RangeInclusive<T>> = Vec::with_capacity(ranges.len());
+		for range in ranges {
+			let adjacent = merged.last().is_some_and(|last| {
+				last.end().successor().is_none_or(|next| *range.start() <= next)
+			});
+			match merged.last_mut() {
+				Some(last) if adjacent => {
+					if range.end() > last.end() {
+						*last = *last.start()..=*range.end();
+					}
+				},
+				_ => merged.push(range),
+			}
+		}
+		IntervalSet { ranges: merged }
+	}
+
+	pub fn insert(&mut self, element: T) {
+		self.insert_range(element..=element);
+	}
+
+	pub fn insert_range(&mut self, range: RangeInclusive<T>) {
+		let mut ranges = std::mem::take(&mut self.ranges);
+		ranges.push(range);
+		*self = IntervalSet::from_ranges(ranges);
+	}
+
+	pub fn ranges(&self) -> &[RangeInclusive<T>] {
+		&self.ranges
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.ranges.is_empty()
+	}
+
+	/// Number of elements (which may not fit in a u64, like all the i64).
+	pub fn len(&self) -> u128 {
+		self.ranges.iter().map(|range| T::count(*range.start(), *range.end())).sum()
+	}
+
+	pub fn contains(&self, element: &T) -> bool {
+		let index = self.ranges.partition_point(|range| range.end() < element);
+		self.ranges.get(index).is_some_and(|range| range.contains(element))
+	}
+
+	/// Every element in order.
+	pub fn iter(&self) -> impl Iterator<Item = T> + '_
+	where RangeInclusive<T>: Iterator<Item = T> {
+		self.ranges.iter().flat_map(|range| range.clone())
+	}
+
+	/// Elements of either set for which `member` is true, given whether
+	/// each set has them.
+	fn combine_pair(&self, other: &Self, member: fn(bool, bool) -> bool) -> Self {
+		let ranges = self.ranges.iter().chain(other.ranges.iter());
+		match (ranges.clone().map(|range| *range.start()).min(),
+			ranges.map(|range| *range.end()).max()) {
+			(Some(start), Some(end)) => IntervalSet::combine(&[self, other], &(start..=end),
+				|values| member(values[0], values[1])),
+			_ => IntervalSet::new(),
+		}
+	}
+
+	/// Elements of the universe for which `member` is true, given whether
+	/// each set has them. The universe is cut in segments where every set
+	/// either has all of the elements or none, so `member` is called once for
+	/// each segment.
+	fn combine(
+		sets: &[&Self],
+		universe: &RangeInclusive<T>,
+		mut member: impl FnMut(&[bool]) -> bool,
+	) -> Self {
+		if universe.is_empty() {
+			return IntervalSet::new();
+		}
+		let mut starts: Vec<T> = vec![*universe.start()];
+		for range in sets.iter().flat_map(|set| set.ranges.iter()) {
+			starts.push(*range.start());
+			starts.extend(range.end().successor());
+		}
+		starts.retain(|start| universe.contains(start));
+		starts.sort_unstable();
+		starts.dedup();
+		let mut ranges: Vec<RangeInclusive<T>> = vec![];
+		let mut values: Vec<bool> = vec![false; sets.len()];
+		for (index, start) in starts.iter().enumerate() {
+			let end = match starts.get(index + 1) {
+				Some(next) => next.predecessor().unwrap_or(*start),
+				None => *universe.end(),
+			};
+			for (value, set) in values.iter_mut().zip(sets) {
+				*value = set.contains(start);
+			}
+			if member(&values) {
+				ranges.push(*start..=end);
+			}
+		}
+		IntervalSet::from_ranges(ranges)
+	}
+
+	pub fn complement(&self, universe: &RangeInclusive<T>) -> Self {
+		IntervalSet::combine(&[self], universe, |values| !values[0])
+	}
+
+	pub fn intersection(&self, other: &Self) -> Self {
+		self.combine_pair(other, |a, b| a && b)
+	}
+
+	pub fn union(&self, other: &Self) -> Self {
+		IntervalSet::from_ranges(self.ranges.iter().chain(other.ranges.iter()).cloned())
+	}
+
+	pub fn symmetric_difference(&self, other: &Self) -> Self {
+		self.combine_pair(other, |a, b| a != b)
+	}
+
+	pub fn difference(&self, other: &Self) -> Self {
+		self.combine_pair(other, |a, b| a && !b)
+	}
+
+	pub fn implication(&self, other: &Self, universe: &RangeInclusive<T>) -> Self {
+		IntervalSet::combine(&[self, other], universe, |values| !values[0] || values[1])
+	}
+
+	pub fn equivalence(&self, other: &Self, universe: &RangeInclusive<T>) -> Self {
+		IntervalSet::combine(&[self, other], universe, |values| values[0] == values[1])
+	}
+
+	pub fn is_subset(&self, other: &Self) -> bool {
+		self.difference(other).is_empty()
+	}
+}
+
+/// Evaluate a set formula, like `eval_set_in`, on interval sets (the first
+/// set given is 'A', the second 'B' and so on), the complement being taken
+/// in the given universe. Every element of a segment where each set either
+/// has all the elements or none of them is in the same sets, so the formula
+/// is evaluated once for each segment and not for each element. The formula
+/// has the same syntax as for `eval_set`.
+pub fn eval_interval_set<T: Discrete>(
+	formula: &str,
+	sets: &[IntervalSet<T>],
+	universe: RangeInclusive<T>,
+) -> IntervalSet<T> {
+	if formula.is_empty() {
+		panic!("formula string is empty");
+	}
+	let keys = set_variables(formula, sets.len());
+	check_set_formula(formula);
+	let u = IntervalSet::from(universe.clone());
+	for (key, set) in keys.iter().zip(sets) {
+		if !set.is_subset(&u) {
+			panic!("set '{}' is not a subset of the universe", key);
+		}
+	}
+	let tree = BoolNode::tree(formula);
+	let sets: Vec<&IntervalSet<T>> = sets.iter().collect();
+	IntervalSet::combine(&sets, &universe, |values| {
+		let assignment = keys.iter().zip(values)
+			.filter(|(_, value)| **value)
+			.fold(0u32, |assignment, (key, _)| assignment | 1 << (*key as u32 - 'A' as u32));
+		tree.eval(&assignment)
+	})
+}
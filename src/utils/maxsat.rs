@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use super::ast::BoolNode;
+use super::sat::{self, Clause, Literal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weight {
+	/// Must be satisfied.
+	Hard,
+	/// Should be satisfied, falsifying it costs the given weight.
+	Soft(u64),
+}
+
+/// Soft constraint made of one or more clauses, satisfied only when every
+/// one of its clauses is.
+#[derive(Debug, Clone)]
+struct SoftGroup {
+	clauses: Vec<Clause>,
+	weight: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+	pub assignment: HashMap<char, bool>,
+	/// Total weight of the satisfied soft constraints.
+	pub satisfied: u64,
+	/// Total weight of the falsified soft constraints.
+	pub cost: u64,
+}
+
+/// Weighted MaxSAT problem: find an assignment satisfying every hard clause
+/// and maximizing the weight of the satisfied soft constraints.
+#[derive(Debug, Clone, Default)]
+pub struct MaxSat {
+	hard: Vec<Clause>,
+	soft: Vec<SoftGroup>,
+}
+
+struct Search {
+	best: Option<(u64, HashMap<char, bool>)>,
+}
+
+fn apply(
+	hard: &[Clause],
+	soft: &[SoftGroup],
+	literal: Literal,
+) -> (Vec<Clause>, Vec<SoftGroup>, u64) {
+	let mut falsified: u64 = 0;
+	let mut remaining: Vec<SoftGroup> = vec![];
+
+	for group in soft {
+		let clauses = sat::assign(&group.clauses, literal);
+		if clauses.iter().any(|clause| clause.is_empty()) {
+			falsified += group.weight;
+		} else if !clauses.is_empty() {
+			remaining.push(SoftGroup { clauses, weight: group.weight });
+		}
+	}
+	(sat::assign(hard, literal), remaining, falsified)
+}
+
+impl Search {
+	fn branch(
+		&mut self,
+		mut hard: Vec<Clause>,
+		mut soft: Vec<SoftGroup>,
+		mut cost: u64,
+		mut assignment: HashMap<char, bool>,
+	) {
+		loop {
+			if hard.iter().any(|clause| clause.is_empty()) { return }
+			if let Some((best_cost, _)) = &self.best {
+				if cost >= *best_cost { return }
+			}
+			let unit = match hard.iter().find(|clause| clause.len() == 1) {
+				Some(clause) => clause[0],
+				None => break,
+			};
+			assignment.insert(unit.variable, unit.positive);
+			let (new_hard, new_soft, falsified) = apply(&hard, &soft, unit);
+			hard = new_hard;
+			soft = new_soft;
+			cost += falsified;
+		}
+		let literal = match (hard.first(), soft.first()) {
+			(Some(clause), _) => clause[0],
+			(None, Some(group)) => group.clauses[0][0],
+			(None, None) => {
+				self.best = Some((cost, assignment));
+				return
+			},
+		};
+		for choice in [literal, literal.negated()].iter() {
+			let (new_hard, new_soft, falsified) = apply(&hard, &soft, *choice);
+			let mut branch = assignment.clone();
+			branch.insert(choice.variable, choice.positive);
+			self.branch(new_hard, new_soft, cost + falsified, branch);
+		}
+	}
+}
+
+impl MaxSat {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn add_clause(&mut self, clause: Clause, weight: Weight) {
+		match weight {
+			Weight::Hard => self.hard.push(clause),
+			Weight::Soft(weight) => {
+				self.soft.push(SoftGroup { clauses: vec![clause], weight });
+			},
+		}
+	}
+
+	/// Add a formula as a single constraint. It is converted to clauses with
+	/// `to_cnf` and a soft formula only counts as satisfied if all of its
+	/// clauses are.
+	pub fn add_formula(&mut self, formula: &BoolNode, weight: Weight) {
+		let mut clauses = sat::clauses(formula);
+		match weight {
+			Weight::Hard => self.hard.append(&mut clauses),
+			Weight::Soft(weight) => {
+				self.soft.push(SoftGroup { clauses, weight });
+			},
+		}
+	}
+
+	/// Add each top-level conjunct of the formula as a separate constraint,
+	/// the weight of each one being given by `weights` in the same order.
+	pub fn add_conjuncts(&mut self, formula: &BoolNode, weights: &[Weight]) {
+		let conjuncts = formula.conjuncts();
+		if conjuncts.len() != weights.len() {
+			panic!("{} weights given for {} conjuncts", weights.len(),
+				conjuncts.len());
+		}
+		for (conjunct, weight) in conjuncts.into_iter().zip(weights.iter()) {
+			self.add_formula(conjunct, *weight);
+		}
+	}
+
+	/// Exact branch and bound search. Returns None if the hard clauses can
+	/// not be satisfied.
+	pub fn solve(&self) -> Option<Solution> {
+		let total: u64 = self.soft.iter().map(|group| group.weight).sum();
+		let mut soft: Vec<SoftGroup> = vec![];
+		let mut cost: u64 = 0;
+
+		for group in &self.soft {
+			if group.clauses.iter().any(|clause| clause.is_empty()) {
+				cost += group.weight;
+			} else if !group.clauses.is_empty() {
+				soft.push(group.clone());
+			}
+		}
+		let mut search = Search { best: None };
+		search.branch(self.hard.clone(), soft, cost, HashMap::new());
+		let (cost, mut assignment) = search.best?;
+		let groups = self.soft.iter().flat_map(|group| group.clauses.iter());
+		for literal in self.hard.iter().chain(groups).flatten() {
+			assignment.entry(literal.variable).or_insert(false);
+		}
+		Some(Solution { assignment, satisfied: total - cost, cost })
+	}
+}
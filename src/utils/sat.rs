@@ -0,0 +1,637 @@
+use std::collections::HashMap;
+use std::fmt;
+use super::ast::{BoolNode, BoolType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+	pub variable: char,
+	pub positive: bool,
+}
+
+impl Literal {
+	pub fn new(variable: char, positive: bool) -> Self {
+		Self { variable, positive }
+	}
+
+	pub fn negated(self) -> Self {
+		Self { variable: self.variable, positive: !self.positive }
+	}
+
+	pub fn to_node(self) -> BoolNode {
+		let variable = BoolNode::variable(self.variable);
+		if self.positive { variable } else { BoolNode::unary('!', variable) }
+	}
+}
+
+impl fmt::Display for Literal {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.positive {
+			write!(f, "{}", self.variable)
+		} else {
+			write!(f, "{}!", self.variable)
+		}
+	}
+}
+
+/// Disjunction of literals. An empty clause is unsatisfiable.
+pub type Clause = Vec<Literal>;
+
+pub fn clause_to_node(clause: &[Literal]) -> BoolNode {
+	BoolNode::disjunction(clause.iter().map(|literal| literal.to_node()).collect())
+}
+
+pub fn clauses_to_node(clauses: &[Clause]) -> BoolNode {
+	BoolNode::conjunction(clauses.iter().map(|c| clause_to_node(c)).collect())
+}
+
+/// Either the literal of the node or its value if it is a constant.
+fn literal_value(node: &BoolNode) -> Result<Literal, bool> {
+	match (node.boolean_type(), node.left()) {
+		(BoolType::Variable, _) => Ok(Literal::new(node.op_symbol(), true)),
+		(BoolType::True, _) => Err(true),
+		(BoolType::False, _) => Err(false),
+		(BoolType::Negation, Some(child)) => {
+			match literal_value(child) {
+				Ok(literal) if child.boolean_type() == BoolType::Variable => {
+					Ok(literal.negated())
+				},
+				Err(value) => Err(!value),
+				_ => panic!("'{}' is not a literal", node.to_formula()),
+			}
+		},
+		_ => panic!("'{}' is not a literal", node.to_formula()),
+	}
+}
+
+/// Read the clauses of a formula which is already in CNF. Constants are
+/// removed, as well as tautological clauses and duplicate literals.
+pub fn cnf_to_clauses(cnf: &BoolNode) -> Vec<Clause> {
+	let mut clauses: Vec<Clause> = vec![];
+
+	'clauses: for conjunct in cnf.conjuncts() {
+		let mut clause: Clause = vec![];
+		for disjunct in conjunct.disjuncts() {
+			match literal_value(disjunct) {
+				Ok(literal) => {
+					if clause.contains(&literal.negated()) { continue 'clauses }
+					if !clause.contains(&literal) { clause.push(literal) }
+				},
+				Err(true) => continue 'clauses,
+				Err(false) => (),
+			}
+		}
+		clauses.push(clause);
+	}
+	clauses
+}
+
+/// Convert any formula to CNF with `to_cnf_nary` and return its clauses.
+pub fn clauses(formula: &BoolNode) -> Vec<Clause> {
+	let mut cnf = formula.clone();
+	cnf.to_cnf_nary();
+	cnf_to_clauses(&cnf)
+}
+
+/// Simplify the clauses knowing that the literal is true: the clauses which
+/// contain it are removed and its negation is removed from the others.
+pub fn assign(clauses: &[Clause], literal: Literal) -> Vec<Clause> {
+	let negation = literal.negated();
+	clauses.iter()
+		.filter(|clause| !clause.contains(&literal))
+		.map(|clause| {
+			clause.iter().filter(|l| **l != negation).copied().collect()
+		})
+		.collect()
+}
+
+fn dpll(mut clauses: Vec<Clause>, assignment: &mut HashMap<char, bool>) -> bool {
+	loop {
+		if clauses.iter().any(|clause| clause.is_empty()) { return false }
+		match clauses.iter().find(|clause| clause.len() == 1) {
+			Some(unit) => {
+				let literal = unit[0];
+				assignment.insert(literal.variable, literal.positive);
+				clauses = assign(&clauses, literal);
+			},
+			None => break,
+		}
+	}
+	if clauses.is_empty() { return true }
+	let literal = clauses[0][0];
+	for choice in [literal, literal.negated()].iter() {
+		let mut branch = assignment.clone();
+		branch.insert(choice.variable, choice.positive);
+		if dpll(assign(&clauses, *choice), &mut branch) {
+			*assignment = branch;
+			return true
+		}
+	}
+	false
+}
+
+/// Find a model of the clauses with the DPLL algorithm. Every variable of
+/// the clauses is given a value in the returned assignment.
+pub fn solve(clauses: &[Clause]) -> Option<HashMap<char, bool>> {
+	let mut assignment: HashMap<char, bool> = HashMap::new();
+
+	if !dpll(clauses.to_vec(), &mut assignment) { return None }
+	for literal in clauses.iter().flatten() {
+		assignment.entry(literal.variable).or_insert(false);
+	}
+	Some(assignment)
+}
+
+pub fn satisfiable(formula: &BoolNode) -> bool {
+	solve(&clauses(formula)).is_some()
+}
+
+/// Deletion based core extraction: each group is dropped in turn and kept
+/// out if the remaining ones are still unsatisfiable. The result is minimal,
+/// meaning that removing any of its groups makes it satisfiable, but it is not
+/// necessarily the smallest core.
+fn minimize_core(groups: &[Vec<Clause>]) -> Option<Vec<usize>> {
+	let all_clauses: Vec<Clause> = groups.iter().flatten().cloned().collect();
+	if solve(&all_clauses).is_some() { return None }
+	let mut core: Vec<usize> = (0..groups.len()).collect();
+	let mut index = 0;
+	while index < core.len() {
+		let candidate: Vec<Clause> = core.iter().enumerate()
+			.filter(|(position, _)| *position != index)
+			.flat_map(|(_, group)| groups[*group].iter().cloned())
+			.collect();
+		if solve(&candidate).is_none() {
+			core.remove(index);
+		} else {
+			index += 1;
+		}
+	}
+	Some(core)
+}
+
+/// Indexes of a minimal subset of rules whose conjunction is unsatisfiable,
+/// or None if every rule can be satisfied at the same time.
+pub fn unsat_core(rules: &[BoolNode]) -> Option<Vec<usize>> {
+	let groups: Vec<Vec<Clause>> = rules.iter().map(clauses).collect();
+	minimize_core(&groups)
+}
+
+/// Minimal unsatisfiable subset of the top-level conjuncts of the formula.
+pub fn conjunct_core(formula: &BoolNode) -> Option<Vec<BoolNode>> {
+	let rules: Vec<BoolNode> = formula.conjuncts().into_iter().cloned().collect();
+	let core = unsat_core(&rules)?;
+	Some(core.into_iter().map(|index| rules[index].clone()).collect())
+}
+
+/// Minimal unsatisfiable subset of the CNF clauses of the formula.
+pub fn clause_core(formula: &BoolNode) -> Option<Vec<Clause>> {
+	let all_clauses = clauses(formula);
+	let groups: Vec<Vec<Clause>> = all_clauses.iter()
+		.map(|clause| vec![clause.clone()])
+		.collect();
+	let core = minimize_core(&groups)?;
+	Some(core.into_iter().map(|index| all_clauses[index].clone()).collect())
+}
+
+/// Craig interpolant of an unsatisfiable conjunction A ∧ B: a formula I over
+/// the variables shared by A and B such that A ⇒ I and I ∧ B is
+/// unsatisfiable. This is the strongest one, computed by existentially
+/// quantifying the variables which are only in A. Returns None if A ∧ B is
+/// satisfiable.
+pub fn interpolant(a: &BoolNode, b: &BoolNode) -> Option<BoolNode> {
+	let mut conjunction = clauses(a);
+	conjunction.append(&mut clauses(b));
+	if solve(&conjunction).is_some() { return None }
+	let shared = b.free_variables();
+	let mut result = a.clone();
+	for variable in a.free_variables() {
+		if !shared.contains(&variable) {
+			result = BoolNode::exists(variable, result);
+		}
+	}
+	result.eliminate_quantifiers();
+	Some(result)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+	Satisfiable(HashMap<char, bool>),
+	/// Holds assumptions which are contradicted by the clauses, in the order
+	/// they were given (a subset of them which is enough for the
+	/// contradiction, not necessarily a minimal one), empty if the clauses
+	/// are unsatisfiable on their own.
+	Unsatisfiable(Vec<Literal>),
+}
+
+/// Incremental CDCL solving session. Everything is kept from one call to the
+/// next: the clauses with their two watched literals, the learned clauses,
+/// the assignments which hold whatever the assumptions (decision level 0)
+/// and the last value of each variable, which is tried first. Assumptions
+/// are the first decisions of the search, so the failed ones are read from
+/// the conflict, without solving again. The clauses added in a scope all
+/// have the negation of a selector variable, which is assumed while the
+/// scope is open: the clauses learned from them have it too, and are
+/// dropped with the scope.
+#[derive(Debug, Clone)]
+pub struct Solver {
+	/// Clauses as they were added.
+	clauses: Vec<Clause>,
+	/// Number of clauses and selector variable of each open scope.
+	scopes: Vec<(usize, usize)>,
+	/// Name of each variable, None for the selectors.
+	names: Vec<Option<char>>,
+	indexes: HashMap<char, usize>,
+	/// Selectors of the closed scopes, which have no clause left.
+	spare_selectors: Vec<usize>,
+	/// Clauses added and learned, over literal codes (twice the variable,
+	/// plus one for a negative literal). The first two literals are watched.
+	database: Vec<Vec<usize>>,
+	/// Clauses watching each literal code, visited when it becomes false.
+	watches: Vec<Vec<usize>>,
+	values: Vec<Option<bool>>,
+	levels: Vec<usize>,
+	/// Clause which implied the value of each variable, None for decisions.
+	reasons: Vec<Option<usize>>,
+	phases: Vec<bool>,
+	activities: Vec<f64>,
+	bump: f64,
+	trail: Vec<usize>,
+	/// Start of each decision level in the trail.
+	limits: Vec<usize>,
+	/// Next literal of the trail to propagate.
+	head: usize,
+	/// The clauses out of any scope are unsatisfiable.
+	unsatisfiable: bool,
+}
+
+impl Default for Solver {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+fn variable(code: usize) -> usize {
+	code >> 1
+}
+
+fn value(values: &[Option<bool>], code: usize) -> Option<bool> {
+	values[variable(code)].map(|value| value == (code & 1 == 0))
+}
+
+impl Solver {
+	pub fn new() -> Self {
+		Self {
+			clauses: vec![],
+			scopes: vec![],
+			names: vec![],
+			indexes: HashMap::new(),
+			spare_selectors: vec![],
+			database: vec![],
+			watches: vec![],
+			values: vec![],
+			levels: vec![],
+			reasons: vec![],
+			phases: vec![],
+			activities: vec![],
+			bump: 1.0,
+			trail: vec![],
+			limits: vec![],
+			head: 0,
+			unsatisfiable: false,
+		}
+	}
+
+	fn new_variable(&mut self, name: Option<char>) -> usize {
+		let index = self.names.len();
+		self.names.push(name);
+		self.watches.extend([vec![], vec![]]);
+		self.values.push(None);
+		self.levels.push(0);
+		self.reasons.push(None);
+		self.phases.push(false);
+		self.activities.push(0.0);
+		index
+	}
+
+	fn code(&mut self, literal: Literal) -> usize {
+		let index = match self.indexes.get(&literal.variable) {
+			Some(index) => *index,
+			None => {
+				let index = self.new_variable(Some(literal.variable));
+				self.indexes.insert(literal.variable, index);
+				index
+			},
+		};
+		index << 1 | usize::from(!literal.positive)
+	}
+
+	fn level(&self) -> usize {
+		self.limits.len()
+	}
+
+	fn enqueue(&mut self, code: usize, reason: Option<usize>) {
+		let index = variable(code);
+		self.values[index] = Some(code & 1 == 0);
+		self.levels[index] = self.level();
+		self.reasons[index] = reason;
+		self.trail.push(code);
+	}
+
+	fn backtrack(&mut self, level: usize) {
+		if self.level() <= level { return }
+		for code in self.trail.drain(self.limits[level]..) {
+			let index = variable(code);
+			self.phases[index] = code & 1 == 0;
+			self.values[index] = None;
+			self.reasons[index] = None;
+		}
+		self.limits.truncate(level);
+		self.head = self.trail.len();
+	}
+
+	/// Store a clause at level 0, watching literals which are not false if
+	/// there are any, and propagate it if it is a unit.
+	fn attach(&mut self, mut literals: Vec<usize>) {
+		literals.sort_unstable();
+		literals.dedup();
+		if literals.windows(2).any(|pair| pair[0] ^ 1 == pair[1]) { return }
+		literals.sort_by_key(|code| value(&self.values, *code) == Some(false));
+		let index = self.database.len();
+		let first = literals.first().copied();
+		let unit = literals.get(1)
+			.is_none_or(|code| value(&self.values, *code) == Some(false));
+		if literals.len() > 1 {
+			self.watches[literals[0]].push(index);
+			self.watches[literals[1]].push(index);
+		}
+		self.database.push(literals);
+		match first.map(|code| value(&self.values, code)) {
+			None | Some(Some(false)) => self.unsatisfiable = true,
+			Some(None) if unit => {
+				self.enqueue(first.unwrap(), Some(index));
+				if self.propagate().is_some() {
+					self.unsatisfiable = true;
+				}
+			},
+			_ => (),
+		}
+	}
+
+	/// Assign the literals implied by the trail, returning the clause which
+	/// is false if there is a conflict.
+	fn propagate(&mut self) -> Option<usize> {
+		while self.head < self.trail.len() {
+			let false_code = self.trail[self.head] ^ 1;
+			self.head += 1;
+			let mut watchers = std::mem::take(&mut self.watches[false_code]);
+			let mut position = 0;
+			while position < watchers.len() {
+				let index = watchers[position];
+				let literals = &mut self.database[index];
+				if literals[0] == false_code {
+					literals.swap(0, 1);
+				}
+				let first = literals[0];
+				if value(&self.values, first) == Some(true) {
+					position += 1;
+					continue
+				}
+				let values = &self.values;
+				let replacement = (2..literals.len())
+					.find(|k| value(values, literals[*k]) != Some(false));
+				match replacement {
+					Some(k) => {
+						literals.swap(1, k);
+						self.watches[literals[1]].push(index);
+						watchers.swap_remove(position);
+					},
+					None if value(&self.values, first) == Some(false) => {
+						self.watches[false_code] = watchers;
+						self.head = self.trail.len();
+						return Some(index);
+					},
+					None => {
+						self.enqueue(first, Some(index));
+						position += 1;
+					},
+				}
+			}
+			self.watches[false_code] = watchers;
+		}
+		None
+	}
+
+	fn bump_activity(&mut self, index: usize) {
+		self.activities[index] += self.bump;
+		if self.activities[index] > 1e100 {
+			for activity in self.activities.iter_mut() {
+				*activity *= 1e-100;
+			}
+			self.bump *= 1e-100;
+		}
+	}
+
+	/// First unique implication point learning: the learned clause has the
+	/// negation of a single literal of the current level, which it asserts
+	/// once the search is back to the level returned.
+	fn analyze(&mut self, conflict: usize) -> (Vec<usize>, usize) {
+		let mut seen = vec![false; self.names.len()];
+		let mut learned: Vec<usize> = vec![0];
+		let mut pending = 0;
+		let mut clause = conflict;
+		let mut cursor = self.trail.len();
+		let mut implied: Option<usize> = None;
+		loop {
+			for position in 0..self.database[clause].len() {
+				let code = self.database[clause][position];
+				let index = variable(code);
+				if Some(code) == implied || seen[index] || self.levels[index] == 0 {
+					continue
+				}
+				seen[index] = true;
+				self.bump_activity(index);
+				if self.levels[index] == self.level() {
+					pending += 1;
+				} else {
+					learned.push(code);
+				}
+			}
+			loop {
+				cursor -= 1;
+				if seen[variable(self.trail[cursor])] { break }
+			}
+			let code = self.trail[cursor];
+			seen[variable(code)] = false;
+			pending -= 1;
+			if pending == 0 {
+				learned[0] = code ^ 1;
+				break
+			}
+			implied = Some(code);
+			clause = self.reasons[variable(code)].expect("implied literal");
+		}
+		self.bump /= 0.95;
+		let mut level = 0;
+		if let Some(highest) = (1..learned.len())
+			.max_by_key(|position| self.levels[variable(learned[*position])]) {
+			learned.swap(1, highest);
+			level = self.levels[variable(learned[1])];
+		}
+		(learned, level)
+	}
+
+	/// Assumptions which imply the negation of the given one (which is
+	/// false), along with it.
+	fn analyze_final(&self, assumption: usize) -> Vec<usize> {
+		let mut failed = vec![assumption];
+		let mut seen = vec![false; self.names.len()];
+		seen[variable(assumption)] = true;
+		for code in self.trail[self.limits.first().copied().unwrap_or(0)..].iter().rev() {
+			let index = variable(*code);
+			if !seen[index] { continue }
+			match self.reasons[index] {
+				None => failed.push(*code),
+				Some(reason) => {
+					for other in self.database[reason].iter() {
+						if self.levels[variable(*other)] > 0 {
+							seen[variable(*other)] = true;
+						}
+					}
+				},
+			}
+		}
+		failed
+	}
+
+	/// Unassigned named variable with the highest activity.
+	fn pick_branch(&self) -> Option<usize> {
+		(0..self.names.len())
+			.filter(|index| self.values[*index].is_none() && self.names[*index].is_some())
+			.max_by(|a, b| self.activities[*a].total_cmp(&self.activities[*b]))
+	}
+
+	pub fn add_clause(&mut self, clause: Clause) {
+		let mut literals: Vec<usize> = clause.iter()
+			.map(|literal| self.code(*literal))
+			.collect();
+		if let Some((_, selector)) = self.scopes.last() {
+			literals.push(selector << 1 | 1);
+		}
+		self.clauses.push(clause);
+		self.attach(literals);
+	}
+
+	pub fn add_formula(&mut self, formula: &BoolNode) {
+		for clause in clauses(formula) {
+			self.add_clause(clause);
+		}
+	}
+
+	/// Open a new scope: everything added after this is removed by `pop`.
+	pub fn push(&mut self) {
+		let selector = match self.spare_selectors.pop() {
+			Some(selector) => selector,
+			None => self.new_variable(None),
+		};
+		self.scopes.push((self.clauses.len(), selector));
+	}
+
+	/// Close the last scope, dropping its clauses and every clause learned
+	/// from them. The assignments of level 0 are computed again.
+	pub fn pop(&mut self) {
+		let (clause_count, selector) = match self.scopes.pop() {
+			Some(scope) => scope,
+			None => panic!("no scope to pop"),
+		};
+		self.clauses.truncate(clause_count);
+		self.spare_selectors.push(selector);
+		let database = std::mem::take(&mut self.database);
+		self.backtrack(0);
+		for code in self.trail.drain(..) {
+			self.values[variable(code)] = None;
+			self.reasons[variable(code)] = None;
+		}
+		self.head = 0;
+		self.unsatisfiable = false;
+		for watchers in self.watches.iter_mut() {
+			watchers.clear();
+		}
+		for literals in database {
+			if !literals.iter().any(|code| variable(*code) == selector) {
+				self.attach(literals);
+			}
+		}
+	}
+
+	pub fn scope_depth(&self) -> usize {
+		self.scopes.len()
+	}
+
+	pub fn clauses(&self) -> &[Clause] {
+		&self.clauses
+	}
+
+	/// Solve the clauses with the assumption literals taken as true. The
+	/// model gives a value to every variable the session has seen.
+	pub fn solve(&mut self, assumptions: &[Literal]) -> Outcome {
+		if self.unsatisfiable { return Outcome::Unsatisfiable(vec![]) }
+		let mut assumed: Vec<usize> = self.scopes.iter()
+			.map(|(_, selector)| selector << 1)
+			.collect();
+		for literal in assumptions {
+			let code = self.code(*literal);
+			assumed.push(code);
+		}
+		loop {
+			if let Some(conflict) = self.propagate() {
+				if self.level() == 0 {
+					self.unsatisfiable = true;
+					return Outcome::Unsatisfiable(vec![]);
+				}
+				let (learned, level) = self.analyze(conflict);
+				self.backtrack(level);
+				let asserting = learned[0];
+				let index = self.database.len();
+				if learned.len() > 1 {
+					self.watches[learned[0]].push(index);
+					self.watches[learned[1]].push(index);
+				}
+				self.database.push(learned);
+				self.enqueue(asserting, Some(index));
+				continue
+			}
+			let decision = match assumed.get(self.level()) {
+				Some(code) => match value(&self.values, *code) {
+					Some(true) => {
+						self.limits.push(self.trail.len());
+						continue
+					},
+					Some(false) => {
+						let failed = self.analyze_final(*code);
+						self.backtrack(0);
+						return Outcome::Unsatisfiable(assumptions.iter()
+							.filter(|literal| {
+								let code = self.indexes[&literal.variable] << 1
+									| usize::from(!literal.positive);
+								failed.contains(&code)
+							})
+							.copied()
+							.collect());
+					},
+					None => *code,
+				},
+				None => match self.pick_branch() {
+					Some(index) => index << 1 | usize::from(!self.phases[index]),
+					None => {
+						let model = self.indexes.iter()
+							.map(|(name, index)| (*name, self.values[*index] == Some(true)))
+							.collect();
+						self.backtrack(0);
+						return Outcome::Satisfiable(model);
+					},
+				},
+			};
+			self.limits.push(self.trail.len());
+			self.enqueue(decision, None);
+		}
+	}
+}
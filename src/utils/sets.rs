@@ -1,38 +1,303 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::ops::RangeInclusive;
+use super::ast::{BoolNode, BoolType};
 
-fn sub_powerset(set: &[i32], template: u128)-> Vec<i32> {
-	let mut shift = 0;
-	let mut sub_powerset: Vec<i32> = vec![];
+/// Unsigned integer of any size, counting the subsets of a set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Counter {
+	/// Little endian words, without any zero word at the end.
+	words: Vec<u64>,
+}
+
+impl Counter {
+	fn from_usize(value: usize) -> Self {
+		let mut counter = Counter { words: vec![value as u64] };
+		counter.trim();
+		counter
+	}
+
+	fn power_of_two(exponent: usize) -> Self {
+		let mut words = vec![0; exponent / 64 + 1];
+		words[exponent / 64] = 1 << (exponent % 64);
+		Counter { words }
+	}
+
+	/// Number of subsets of k elements in a set of n elements.
+	fn binomial(n: usize, k: usize) -> Self {
+		if k > n { return Self::from_usize(0) }
+		let mut counter = Self::from_usize(1);
+		for i in 0..k.min(n - k) {
+			counter.mul_small((n - i) as u64);
+			counter.div_small((i + 1) as u64);
+		}
+		counter
+	}
+
+	fn trim(&mut self) {
+		while self.words.last() == Some(&0) {
+			self.words.pop();
+		}
+	}
+
+	fn bit(&self, index: usize) -> bool {
+		self.words.get(index / 64).is_some_and(|word| word >> (index % 64) & 1 != 0)
+	}
+
+	fn add(&mut self, other: &Self) {
+		let mut carry = 0u64;
+		for index in 0..self.words.len().max(other.words.len()) {
+			if index == self.words.len() {
+				self.words.push(0);
+			}
+			let (sum, first) = self.words[index]
+				.overflowing_add(other.words.get(index).copied().unwrap_or(0));
+			let (sum, second) = sum.overflowing_add(carry);
+			self.words[index] = sum;
+			carry = (first || second) as u64;
+		}
+		if carry != 0 {
+			self.words.push(carry);
+		}
+	}
 
-	sub_powerset.extend_from_slice(set);
-	sub_powerset.retain(|_| {
-		let keep = (template >> shift & 1) != 0;
-		shift += 1;
-		keep
-	});
-	sub_powerset
+	/// Difference with a smaller counter.
+	fn sub(&self, other: &Self) -> Self {
+		let mut words = self.words.clone();
+		let mut borrow = 0u64;
+		for (index, word) in words.iter_mut().enumerate() {
+			let (diff, first) = word
+				.overflowing_sub(other.words.get(index).copied().unwrap_or(0));
+			let (diff, second) = diff.overflowing_sub(borrow);
+			*word = diff;
+			borrow = (first || second) as u64;
+		}
+		let mut counter = Counter { words };
+		counter.trim();
+		counter
+	}
+
+	fn mul_small(&mut self, factor: u64) {
+		let mut carry = 0u128;
+		for word in self.words.iter_mut() {
+			let product = *word as u128 * factor as u128 + carry;
+			*word = product as u64;
+			carry = product >> 64;
+		}
+		if carry != 0 {
+			self.words.push(carry as u64);
+		}
+		self.trim();
+	}
+
+	fn div_small(&mut self, divisor: u64) {
+		let mut remainder = 0u128;
+		for word in self.words.iter_mut().rev() {
+			let value = remainder << 64 | *word as u128;
+			*word = (value / divisor as u128) as u64;
+			remainder = value % divisor as u128;
+		}
+		self.trim();
+	}
+
+	/// Reflected binary code of the counter: x ^ (x >> 1).
+	fn gray(&self) -> Self {
+		let mut words = self.words.clone();
+		for (index, word) in words.iter_mut().enumerate() {
+			let high = self.words.get(index + 1).copied().unwrap_or(0);
+			*word ^= self.words[index] >> 1 | high << 63;
+		}
+		let mut counter = Counter { words };
+		counter.trim();
+		counter
+	}
+
+	fn to_usize(&self) -> Option<usize> {
+		match self.words.as_slice() {
+			[] => Some(0),
+			[word] => usize::try_from(*word).ok(),
+			_ => None,
+		}
+	}
 }
 
-pub fn powerset(set: &[i32]) -> Vec<Vec<i32>> {
-	let mut powerset: Vec<Vec<i32>> = vec![];
-	let mut template: u128 = 0;
-	let end: u128;
+impl PartialOrd for Counter {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
 
-	if set.len() >= 128 {
-		panic!("input is waaaaay too big, please calm down");
+impl Ord for Counter {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.words.len().cmp(&other.words.len())
+			.then_with(|| self.words.iter().rev().cmp(other.words.iter().rev()))
 	}
-	end = 1 << set.len();
-	while template < end {
-		powerset.push(sub_powerset(set, template));
-		template += 1;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+	Binary,
+	Gray,
+	Combinations(usize),
+}
+
+/// Lazy iterator on the subsets of a set, of any size. Subsets are given in
+/// one of three orders:
+///
+/// - `new`: binary counting, element i being in the subset if bit i of the
+///   subset number is set (the order of `powerset`)
+/// - `gray`: Gray code order, where two consecutive subsets only differ by
+///   one element
+/// - `combinations`: only the subsets of k elements, in lexicographic order
+///   of the element positions
+///
+/// `nth` jumps to a subset without going through the ones before it, and
+/// `remaining_len` gives the number of subsets left (there is no `len`, as
+/// it does not fit in a `usize` for sets of 64 elements or more).
+#[derive(Debug, Clone)]
+pub struct Powerset<'a, T> {
+	set: &'a [T],
+	order: Order,
+	/// Number of the next subset.
+	position: Counter,
+	end: Counter,
+	/// Positions of the elements of the next combination, if known.
+	indices: Option<Vec<usize>>,
+}
+
+impl<'a, T: Clone> Powerset<'a, T> {
+	pub fn new(set: &'a [T]) -> Self {
+		Self::with_order(set, Order::Binary, Counter::power_of_two(set.len()))
+	}
+
+	pub fn gray(set: &'a [T]) -> Self {
+		Self::with_order(set, Order::Gray, Counter::power_of_two(set.len()))
+	}
+
+	pub fn combinations(set: &'a [T], k: usize) -> Self {
+		Self::with_order(set, Order::Combinations(k), Counter::binomial(set.len(), k))
+	}
+
+	fn with_order(set: &'a [T], order: Order, end: Counter) -> Self {
+		Powerset { set, order, position: Counter::from_usize(0), end, indices: None }
+	}
+
+	fn subset_of_bits(&self, bits: &Counter) -> Vec<T> {
+		self.set.iter().enumerate()
+			.filter(|(index, _)| bits.bit(*index))
+			.map(|(_, element)| element.clone())
+			.collect()
+	}
+
+	/// Positions of the combination of the given rank, in lexicographic order.
+	fn unrank(&self, k: usize, mut rank: Counter) -> Vec<usize> {
+		let n = self.set.len();
+		let mut indices = Vec::with_capacity(k);
+		let mut candidate = 0;
+		for slot in 0..k {
+			loop {
+				let count = Counter::binomial(n - candidate - 1, k - slot - 1);
+				candidate += 1;
+				if rank < count { break }
+				rank = rank.sub(&count);
+			}
+			indices.push(candidate - 1);
+		}
+		indices
+	}
+
+	/// Combination coming after the given one, if there is one.
+	fn next_combination(&self, mut indices: Vec<usize>) -> Option<Vec<usize>> {
+		let (n, k) = (self.set.len(), indices.len());
+		let slot = (0..k).rev().find(|slot| indices[*slot] < n - k + slot)?;
+		indices[slot] += 1;
+		for next in slot + 1..k {
+			indices[next] = indices[next - 1] + 1;
+		}
+		Some(indices)
+	}
+
+	/// Number of subsets left, if it fits in a `usize` (which it does not
+	/// for the subsets of a set of 64 elements or more).
+	pub fn remaining_len(&self) -> Option<usize> {
+		self.remaining().to_usize()
+	}
+
+	fn remaining(&self) -> Counter {
+		if self.position >= self.end {
+			Counter::from_usize(0)
+		} else {
+			self.end.sub(&self.position)
+		}
+	}
+}
+
+impl<'a, T: Clone> Iterator for Powerset<'a, T> {
+	type Item = Vec<T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.position >= self.end { return None }
+		let subset = match self.order {
+			Order::Binary => self.subset_of_bits(&self.position),
+			Order::Gray => self.subset_of_bits(&self.position.gray()),
+			Order::Combinations(k) => {
+				let indices = match self.indices.take() {
+					Some(indices) => indices,
+					None => self.unrank(k, self.position.clone()),
+				};
+				let subset = indices.iter().map(|index| self.set[*index].clone()).collect();
+				self.indices = self.next_combination(indices);
+				subset
+			},
+		};
+		self.position.add(&Counter::from_usize(1));
+		Some(subset)
+	}
+
+	fn nth(&mut self, n: usize) -> Option<Self::Item> {
+		if n > 0 {
+			self.position.add(&Counter::from_usize(n));
+			self.indices = None;
+		}
+		self.next()
+	}
+
+	/// Exact when the number of subsets left fits in a `usize`, only a lower
+	/// bound otherwise.
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		match self.remaining().to_usize() {
+			Some(count) => (count, Some(count)),
+			None => (usize::MAX, None),
+		}
+	}
+}
+
+/// Every subset of the set, the elements being of any type. The list of
+/// subsets must fit in a `Vec`, which limits the set to about 58 elements
+/// on 64-bit targets (and much less in practice, as each subset is stored
+/// too). See `Powerset` to go through them one at a time, whatever the size
+/// of the set.
+pub fn powerset<T: Clone>(set: &[T]) -> Vec<Vec<T>> {
+	let subsets = Powerset::new(set);
+	let fits = subsets.remaining_len()
+		.and_then(|count| count.checked_mul(std::mem::size_of::<Vec<T>>()))
+		.is_some_and(|size| size <= isize::MAX as usize);
+	if !fits {
+		panic!("the powerset of {} elements does not fit in memory", set.len());
 	}
-	powerset
+	subsets.collect()
 }
 
-fn build_varmap(formula: &str, sets: &[Vec<i32>]) -> HashMap<char, Vec<i32>> {
-	let mut index = 0;
+/// Same as `powerset` for sets of integers.
+pub fn powerset_i32(set: &[i32]) -> Vec<Vec<i32>> {
+	powerset(set)
+}
+
+/// Sorted variables of a set formula, checking that there is one set for
+/// each of them.
+pub(crate) fn set_variables(formula: &str, set_count: usize) -> Vec<char> {
 	let mut keys: Vec<char> = Vec::with_capacity(26);
-	let mut varmap: HashMap<char, Vec<i32>> = HashMap::with_capacity(26);
 
 	for c in formula.chars().filter(|c| {
 		match c { 'A'..='Z' => true, _ => false }
@@ -42,128 +307,388 @@ fn build_varmap(formula: &str, sets: &[Vec<i32>]) -> HashMap<char, Vec<i32>> {
 		}
 	}
 	keys.sort();
-	if keys.len() > sets.len() {
+	if keys.len() > set_count {
 		panic!("missing set operands for '{}' formula", formula);
-	} else if sets.len() > keys.len() {
+	} else if set_count > keys.len() {
 		panic!("too many set operands for '{}' formula", formula);
 	}
-	for c in keys {
-		let mut set = sets[index].clone();
+	keys
+}
+
+/// Sets given to the variables of a formula, the first set being given to
+/// the first variable in alphabetical order and so on. Each set is sorted
+/// and has every element once.
+pub fn variable_sets<T: Ord + Clone>(formula: &str, sets: &[Vec<T>]) -> HashMap<char, Vec<T>> {
+	let keys = set_variables(formula, sets.len());
+	let mut varmap: HashMap<char, Vec<T>> = HashMap::with_capacity(26);
+
+	for (c, set) in keys.into_iter().zip(sets) {
+		let mut set = set.clone();
 		set.sort();
+		set.dedup();
 		varmap.insert(c, set);
-		index += 1;
 	}
 	varmap
 }
 
-fn set_complement(a: &Vec<i32>, u: &Vec<i32>) -> Vec<i32> {
-	let mut result: Vec<i32> = vec![];
+/// Elements of two sorted sets for which `keep` is true, given whether each
+/// set has them, in one pass over both sets.
+fn set_merge<T: Ord + Clone>(a: &[T], b: &[T], keep: fn(bool, bool) -> bool) -> Vec<T> {
+	let mut result: Vec<T> = vec![];
+	let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
 
-	for element in u {
-		if a.contains(element) == false {
-			result.push(*element);
+	loop {
+		let order = match (a.peek(), b.peek()) {
+			(Some(x), Some(y)) => x.cmp(y),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => break,
+		};
+		let (element, kept) = match order {
+			Ordering::Less => a.next().map(|x| (x, keep(true, false))),
+			Ordering::Greater => b.next().map(|y| (y, keep(false, true))),
+			Ordering::Equal => a.next().zip(b.next()).map(|(x, _)| (x, keep(true, true))),
+		}.expect("the peeked element is there");
+		if kept {
+			result.push(element.clone());
 		}
 	}
-	result.sort();
 	result
 }
 
-fn set_intersection(a: &Vec<i32>, b: &Vec<i32>) -> Vec<i32> {
-	let mut result: Vec<i32> = vec![];
+fn set_complement<T: Ord + Clone>(a: &[T], u: &[T]) -> Vec<T> {
+	set_merge(a, u, |in_a, in_u| in_u && !in_a)
+}
 
-	for element in a {
-		if b.contains(element) {
-			result.push(*element);
-		}
+fn set_intersection<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+	set_merge(a, b, |in_a, in_b| in_a && in_b)
+}
+
+fn set_union<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+	set_merge(a, b, |in_a, in_b| in_a || in_b)
+}
+
+fn set_xor<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+	set_merge(a, b, |in_a, in_b| in_a != in_b)
+}
+
+fn set_implication<T: Ord + Clone>(a: &[T], b: &[T], u: &[T]) -> Vec<T> {
+	set_union(&set_complement(a, u), b)
+}
+
+/// Elements which are in both sets or in neither, (A ∩ B) ∪ (¬A ∩ ¬B) like
+/// the logical equivalence.
+fn set_equivalence<T: Ord + Clone>(a: &[T], b: &[T], u: &[T]) -> Vec<T> {
+	set_union(&set_intersection(a, b),
+		&set_intersection(&set_complement(a, u), &set_complement(b, u)))
+}
+
+/// Elements of the universe which are in a number of the given sets that
+/// `accept` is true for, like a cardinality constraint.
+fn set_cardinality<T: Ord + Clone>(
+	sets: &[Vec<T>],
+	u: &[T],
+	accept: impl Fn(usize) -> bool,
+) -> Vec<T> {
+	u.iter()
+		.filter(|element| {
+			accept(sets.iter().filter(|set| set.binary_search(element).is_ok()).count())
+		})
+		.cloned()
+		.collect()
+}
+
+fn eval_with_universe<T: Ord + Clone>(
+	tree: &BoolNode,
+	varmap: &HashMap<char, Vec<T>>,
+	u: &[T],
+) -> Vec<T> {
+	if tree.is_quantified() {
+		let mut tree = tree.clone();
+		tree.eliminate_quantifiers();
+		return eval_with_universe(&tree, varmap, u);
 	}
-	result.sort();
-	result
+	tree.fold_operands(&mut |node, children: Vec<Vec<T>>| {
+		match (node.boolean_type(), children.as_slice()) {
+			(BoolType::True, []) => u.to_vec(),
+			(BoolType::False, []) => vec![],
+			(BoolType::Variable, []) => match varmap.get(&node.op_symbol()) {
+				Some(set) => set.clone(),
+				None => panic!("no set for variable '{}'", node.op_symbol()),
+			},
+			(BoolType::Negation, [a]) => set_complement(a, u),
+			(BoolType::Conjunction, [first, rest @ ..]) => rest.iter()
+				.fold(first.clone(), |result, set| set_intersection(&result, set)),
+			(BoolType::Disjunction, [first, rest @ ..]) => rest.iter()
+				.fold(first.clone(), |result, set| set_union(&result, set)),
+			(BoolType::ExclusiveDisjunction, [a, b]) => set_xor(a, b),
+			(BoolType::MaterialCondition, [a, b]) => set_implication(a, b, u),
+			(BoolType::LogicalEquivalence, [a, b]) => set_equivalence(a, b, u),
+			(BoolType::AtMost, sets) => {
+				set_cardinality(sets, u, |count| count <= node.bound())
+			},
+			(BoolType::AtLeast, sets) => {
+				set_cardinality(sets, u, |count| count >= node.bound())
+			},
+			(BoolType::Exactly, sets) => {
+				set_cardinality(sets, u, |count| count == node.bound())
+			},
+			_ => panic!("invalid '{}' op", node.boolean_type()),
+		}
+	})
 }
 
-fn set_union(a: &Vec<i32>, b: &Vec<i32>) -> Vec<i32> {
-	let mut result: Vec<i32> = vec![];
+/// Evaluate a formula tree on the sets given to its variables in `varmap`,
+/// so that it can be rewritten (with `to_nnf`, `to_cnf` and so on) before
+/// being evaluated. The sets are given by variable and not in order since a
+/// rewrite may drop some of them (see `variable_sets` to build the map from
+/// the formula the tree was parsed from). The complement is taken in the
+/// union of every set of the map.
+///
+/// Unlike `eval_set`, every node of a tree can be evaluated: 1 is the
+/// universe and 0 the empty set, a cardinality constraint keeps the elements
+/// which are in the given number of its operand sets, and a quantifier is
+/// expanded into both values of its variable (the universe and the empty
+/// set), so every rewrite of the tree gives the same set.
+pub fn eval_set_tree<T: Ord + Clone>(
+	tree: &BoolNode,
+	varmap: &HashMap<char, Vec<T>>,
+) -> Vec<T> {
+	let mut u: Vec<T> = varmap.values().flatten().cloned().collect();
+	u.sort();
+	u.dedup();
+	let sorted: HashMap<char, Vec<T>> = varmap.iter()
+		.map(|(key, set)| {
+			let mut set = set.clone();
+			set.sort();
+			set.dedup();
+			(*key, set)
+		})
+		.collect();
+	eval_with_universe(tree, &sorted, &u)
+}
+
+/// Check that a set formula only has variables and the operators of
+/// `eval_formula` (so no constant, quantifier or cardinality constraint),
+/// each with its operands.
+pub(crate) fn check_set_formula(formula: &str) {
+	let mut stack_size = 0;
 
-	result.append(&mut a.clone());
-	for element in b {
-		if result.contains(element) == false {
-			result.push(*element);
+	for op in formula.chars() {
+		let operands = match op {
+			'A'..='Z' => 0,
+			'!' => 1,
+			'&' | '|' | '^' | '>' | '=' => 2,
+			_ => panic!("'{}' is not a valid op or is missing an argument", op),
+		};
+		if stack_size < operands {
+			panic!("'{}' is not a valid op or is missing an argument", op);
 		}
+		stack_size = stack_size - operands + 1;
 	}
-	result.sort();
-	result
+	if stack_size > 1 {
+		panic!("the stack should be empty at the end");
+	}
+}
+
+/// Evaluate a set formula, in the same syntax as the boolean formulas, on
+/// sets of any ordered type (the first set given is 'A', the second 'B' and
+/// so on). The complement is taken in the union of every given set, see
+/// `eval_set_in` for an explicit universe. Only the variables and the
+/// operators of `eval_formula` are set operations, see `eval_set_tree` for
+/// the other nodes of a formula tree.
+pub fn eval_set<T: Ord + Clone>(formula: &str, sets: &[Vec<T>]) -> Vec<T> {
+	if formula.is_empty() {
+		panic!("formula string is empty");
+	}
+	let varmap = variable_sets(formula, sets);
+	check_set_formula(formula);
+	eval_set_tree(&BoolNode::tree(formula), &varmap)
+}
+
+/// Set every element is taken from, which the complement and the
+/// implication are computed in. It may be a list, a range or only a
+/// predicate, in which case it cannot be enumerated.
+pub struct Universe<T> {
+	contains: Box<dyn Fn(&T) -> bool>,
+	elements: Option<Enumerator<T>>,
 }
 
-fn set_xor(a: &Vec<i32>, b: &Vec<i32>) -> Vec<i32> {
-	let mut result: Vec<i32> = vec![];
+type Enumerator<T> = Box<dyn Fn() -> Box<dyn Iterator<Item = T>>>;
+
+impl<T: Ord + Clone + 'static> Universe<T> {
+	pub fn finite(mut elements: Vec<T>) -> Self {
+		elements.sort();
+		elements.dedup();
+		let list = elements.clone();
+		Universe {
+			contains: Box::new(move |element| list.binary_search(element).is_ok()),
+			elements: Some(Box::new(move || Box::new(elements.clone().into_iter()))),
+		}
+	}
 
-	for element in a {
-		if b.contains(element) == false {
-			result.push(*element);
+	pub fn range(range: RangeInclusive<T>) -> Self
+	where RangeInclusive<T>: Iterator<Item = T> {
+		let bounds = range.clone();
+		Universe {
+			contains: Box::new(move |element| bounds.contains(element)),
+			elements: Some(Box::new(move || Box::new(range.clone()))),
 		}
 	}
-	for element in b {
-		if a.contains(element) == false {
-			result.push(*element);
+
+	pub fn predicate(predicate: impl Fn(&T) -> bool + 'static) -> Self {
+		Universe { contains: Box::new(predicate), elements: None }
+	}
+
+	pub fn contains(&self, element: &T) -> bool {
+		(self.contains)(element)
+	}
+
+	/// Every element in order, if the universe can be enumerated.
+	pub fn elements(&self) -> Option<Box<dyn Iterator<Item = T>>> {
+		self.elements.as_ref().map(|elements| elements())
+	}
+}
+
+/// Same as `eval_set` with an explicit universe, which every set must be a
+/// subset of. The universe is only enumerated if the result has elements
+/// which are in none of the sets (like the complement of a set), so a
+/// predicate universe only works for formulas where it does not.
+pub fn eval_set_in<T: Ord + Clone + 'static>(
+	formula: &str,
+	sets: &[Vec<T>],
+	universe: &Universe<T>,
+) -> Vec<T> {
+	if formula.is_empty() {
+		panic!("formula string is empty");
+	}
+	let varmap = variable_sets(formula, sets);
+	check_set_formula(formula);
+	let mut keys: Vec<&char> = varmap.keys().collect();
+	keys.sort();
+	for key in keys {
+		if !varmap[key].iter().all(|element| universe.contains(element)) {
+			panic!("set '{}' is not a subset of the universe", key);
+		}
+	}
+	// evaluate on the elements of the sets, None standing for all the others
+	let mut candidates: Vec<T> = sets.concat();
+	candidates.sort();
+	candidates.dedup();
+	let lifted: HashMap<char, Vec<Option<T>>> = varmap.into_iter()
+		.map(|(key, set)| (key, set.into_iter().map(Some).collect()))
+		.collect();
+	let mut u: Vec<Option<T>> = vec![None];
+	u.extend(candidates.iter().cloned().map(Some));
+	let mut result: Vec<T> = vec![];
+	for element in eval_with_universe(&BoolNode::tree(formula), &lifted, &u) {
+		match element {
+			Some(element) => result.push(element),
+			None => match universe.elements() {
+				Some(elements) => result.extend(elements.filter(|element| {
+					candidates.binary_search(element).is_err()
+				})),
+				None => panic!("the result of '{}' is not finite in a predicate universe",
+					formula),
+			},
 		}
 	}
 	result.sort();
 	result
 }
 
-fn set_implication(a: &Vec<i32>, b: &Vec<i32>, u: &Vec<i32>) -> Vec<i32> {
-	set_union(&set_complement(a, u), b)
+/// Same as `eval_set` for sets of integers.
+pub fn eval_set_i32(formula: &str, sets: &[Vec<i32>]) -> Vec<i32> {
+	eval_set(formula, sets)
 }
 
-fn set_equal(a: &Vec<i32>, b: &Vec<i32>) -> Vec<i32> {
-	let mut result: Vec<i32> = vec![];
+type Multiset<T> = BTreeMap<T, usize>;
+
+fn multiset_combine<T: Ord + Clone>(
+	a: &Multiset<T>,
+	b: &Multiset<T>,
+	op: fn(usize, usize) -> usize,
+) -> Multiset<T> {
+	let mut result = Multiset::new();
+	let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
 
-	if a != b {
-		return result
+	// merge both sorted maps, an element missing from one counting as 0
+	loop {
+		let order = match (a.peek(), b.peek()) {
+			(Some((x, _)), Some((y, _))) => x.cmp(y),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => break,
+		};
+		let (element, count) = match order {
+			Ordering::Less => a.next().map(|(x, m)| (x, op(*m, 0))),
+			Ordering::Greater => b.next().map(|(y, n)| (y, op(0, *n))),
+			Ordering::Equal => a.next().zip(b.next()).map(|((x, m), (_, n))| (x, op(*m, *n))),
+		}.expect("the peeked element is there");
+		if count > 0 {
+			result.insert(element.clone(), count);
+		}
 	}
-	result.append(&mut a.clone());
-	result.sort();
 	result
 }
 
-pub fn eval_set(formula: &str, sets: &[Vec<i32>]) -> Vec<i32> {
-	let mut u: Vec<i32> = Vec::new();
-    let mut stack: Vec<Vec<i32>> = Vec::new();
-	let variables: String = ('A'..='Z').collect();
+/// Evaluate a formula on multisets, where an element may be in a set more
+/// than once (the result has each element as many times as it counts).
+/// The intersection '&' keeps the smallest count, the union '|' the largest
+/// one, the sum '+' adds them, the difference '-' subtracts them (down to
+/// zero) and '^' keeps their difference. There is no complement, so '!',
+/// '>' and '=' are not multiset ops.
+pub fn eval_multiset<T: Ord + Clone>(formula: &str, sets: &[Vec<T>]) -> Vec<T> {
+	if formula.is_empty() {
+		panic!("formula string is empty");
+	}
+	let keys = set_variables(formula, sets.len());
+	let mut stack: Vec<Multiset<T>> = Vec::new();
 
-    if formula.len() == 0 {
-        panic!("formula string is empty");
-    }
-	let varmap = build_varmap(formula, sets);
-	for set in sets {
-		for element in set {
-			if u.contains(element) == false {
-				u.push(*element);
+	for op in formula.chars() {
+		if let Some(index) = keys.iter().position(|key| *key == op) {
+			let mut multiset = Multiset::new();
+			for element in &sets[index] {
+				*multiset.entry(element.clone()).or_insert(0) += 1;
 			}
+			stack.push(multiset);
+			continue;
 		}
-	}
-	u.sort();
-    for op in formula.chars() {
-        let right = if op == '!' || variables.contains(op) {
-			None
-		} else {
-			stack.pop()
+		let (b, a) = match (stack.pop(), stack.pop()) {
+			(Some(b), Some(a)) => (b, a),
+			_ => panic!("'{}' is not a valid multiset op or is missing an argument", op),
 		};
-        let left = if variables.contains(op) { None } else { stack.pop() };
-        match (op, left, right) {
-            ('A'..='Z', None, None) => {
-				stack.push(varmap.get(&op).unwrap().clone())
-			},
-            ('!', Some(a), None) => stack.push(set_complement(&a, &u)),
-            ('&', Some(a), Some(b)) => stack.push(set_intersection(&a, &b)),
-            ('|', Some(a), Some(b)) => stack.push(set_union(&a, &b)),
-            ('^', Some(a), Some(b)) => stack.push(set_xor(&a, &b)),
-            ('>', Some(a), Some(b)) => stack.push(set_implication(&a, &b, &u)),
-            ('=', Some(a), Some(b)) => stack.push(set_equal(&a, &b)),
-            _ => panic!("'{}' is not a valid op or is missing an argument", op),
-        }
-    }
-    if stack.len() > 1 {
-        panic!("the stack should be empty at the end");
-    }
-    stack.pop().expect("nothing to return (stack is empty)")
+		let op: fn(usize, usize) -> usize = match op {
+			'&' => usize::min,
+			'|' => usize::max,
+			'+' => |a, b| a + b,
+			'-' => usize::saturating_sub,
+			'^' => |a, b| a.max(b) - a.min(b),
+			_ => panic!("'{}' is not a valid multiset op or is missing an argument", op),
+		};
+		stack.push(multiset_combine(&a, &b, op));
+	}
+	if stack.len() > 1 {
+		panic!("unused operands in formula string");
+	}
+	stack.pop().unwrap_or_default().into_iter()
+		.flat_map(|(element, count)| std::iter::repeat_n(element, count))
+		.collect()
+}
+
+/// Check that the set of formula `a` is a subset of the set of formula `b`,
+/// both being evaluated with `eval_set` on the same sets (so the variables
+/// of both formulas are given a set in order).
+pub fn is_subset<T: Ord + Clone>(a: &str, b: &str, sets: &[Vec<T>]) -> bool {
+	eval_set(&format!("{}{}!&", a, b), sets).is_empty()
+}
+
+/// Same as `is_subset` for the equality of both sets.
+pub fn is_equal<T: Ord + Clone>(a: &str, b: &str, sets: &[Vec<T>]) -> bool {
+	eval_set(&format!("{}{}^", a, b), sets).is_empty()
+}
+
+/// Same as `is_subset`, checking that both sets have no common element.
+pub fn is_disjoint<T: Ord + Clone>(a: &str, b: &str, sets: &[Vec<T>]) -> bool {
+	eval_set(&format!("{}{}&", a, b), sets).is_empty()
 }
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::io::{Write, BufWriter};
+use super::ast::BoolNode;
 
 pub fn eval_formula(formula: &str) -> bool {
     let mut stack: Vec<bool> = Vec::new();
@@ -73,6 +74,52 @@ fn find_truth(formula: &str, varmap: &mut HashMap<char, bool>) {
     varmap.insert('=', eval_formula(&compiled_formula));
 }
 
+/// Quantified formulas cannot be compiled by replacing letters since bound
+/// variables must keep their name, so they are evaluated through the AST.
+fn quantified_tree(formula: &str) -> Option<BoolNode> {
+    if formula.contains(['∃', '∀']) {
+        Some(BoolNode::tree(formula))
+    } else {
+        None
+    }
+}
+
+fn find_keys(
+    formula: &str,
+    tree: &Option<BoolNode>,
+    varmap: &mut HashMap<char, bool>,
+    keys: &mut Vec<char>
+) {
+    let free_variables = tree.as_ref().map(|ast| ast.free_variables());
+
+    for c in formula.chars() {
+        match c {
+            'A'..='Z' => {
+                if let Some(free) = &free_variables {
+                    if !free.contains(&c) { continue }
+                }
+                if varmap.insert(c, false).is_none() { keys.push(c); }
+            },
+            '0' | '1' => panic!("'{}' is not a valid op", c),
+            _ => (),
+        }
+    }
+}
+
+fn eval_truth(
+    formula: &str,
+    tree: &Option<BoolNode>,
+    varmap: &mut HashMap<char, bool>
+) {
+    match tree {
+        Some(ast) => {
+            let truth = ast.eval(varmap);
+            varmap.insert('=', truth);
+        },
+        None => find_truth(formula, varmap),
+    }
+}
+
 fn set_values(varmap: &mut HashMap<char, bool>, keys: &Vec<char>, values: u32) {
     let length: usize = keys.len() - 1;
     if values == 0 { return }
@@ -91,15 +138,8 @@ pub fn table<W: Write>(
 	let mut table: String = String::new();
     let mut values: u32 = 0;
 
-    for c in formula.chars() {
-        match c {
-            'A'..='Z' => {
-                if varmap.insert(c, false) == None { keys.push(c); }
-            },
-            '0' | '1' => panic!("'{}' is not a valid op", c),
-            _ => (),
-        }
-    }
+    let tree = quantified_tree(formula);
+    find_keys(formula, &tree, &mut varmap, &mut keys);
     let values_max: u32 = 1 << keys.len();
     varmap.insert('=', false);
     keys.push('=');
@@ -108,7 +148,7 @@ pub fn table<W: Write>(
     write_truth(&mut table, &mut writer, build_truth(&varmap, &keys, true));
     while values < values_max {
         set_values(&mut varmap, &keys, values);
-        find_truth(formula, &mut varmap);
+        eval_truth(formula, &tree, &mut varmap);
         let truth = build_truth(&varmap, &keys, false);
 		write_truth(&mut table, &mut writer, truth);
         values = values + 1;
@@ -125,15 +165,8 @@ pub fn sat(formula: &str) -> bool {
     let mut keys: Vec<char> = Vec::with_capacity(27);
     let mut values: u32 = 0;
 
-    for c in formula.chars() {
-        match c {
-            'A'..='Z' => {
-                if varmap.insert(c, false) == None { keys.push(c); }
-            },
-            '0' | '1' => panic!("'{}' is not a valid op", c),
-            _ => (),
-        }
-    }
+    let tree = quantified_tree(formula);
+    find_keys(formula, &tree, &mut varmap, &mut keys);
     let values_max: u32 = 1 << keys.len();
     varmap.insert('=', false);
     keys.push('=');
@@ -141,7 +174,7 @@ pub fn sat(formula: &str) -> bool {
     keys.rotate_left(1);
     while values < values_max {
         set_values(&mut varmap, &keys, values);
-        find_truth(formula, &mut varmap);
+        eval_truth(formula, &tree, &mut varmap);
         if *varmap.get(&'=').unwrap() { return true };
         values = values + 1;
     };
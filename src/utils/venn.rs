@@ -0,0 +1,246 @@
+use super::ast::BoolNode;
+use super::sets::{eval_set_tree, set_variables, variable_sets};
+
+/// Atomic region of a Venn diagram: the elements which are in the sets of
+/// the variables set in `mask` and in none of the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region<T> {
+	/// Bit i is set if the region is inside the set of the i-th variable.
+	pub mask: usize,
+	/// Intersection of each set or of its complement, like "AB!&".
+	pub formula: String,
+	pub elements: Vec<T>,
+	/// The formula includes this region.
+	pub selected: bool,
+}
+
+/// Regions a set formula selects in the Venn diagram of its operand sets
+/// (given in the same order as for `eval_set`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Venn<T> {
+	variables: Vec<char>,
+	regions: Vec<Region<T>>,
+}
+
+/// Ellipse of a diagram, rotated by `angle` degrees around its center.
+struct Shape {
+	cx: f64,
+	cy: f64,
+	rx: f64,
+	ry: f64,
+	angle: f64,
+}
+
+const WIDTH: f64 = 400.0;
+const HEIGHT: f64 = 300.0;
+const COLUMNS: usize = 72;
+const ROWS: usize = 28;
+
+const fn circle(cx: f64, cy: f64, r: f64) -> Shape {
+	Shape { cx, cy, rx: r, ry: r, angle: 0.0 }
+}
+
+/// Shapes of the diagrams of one to four sets, each region of which is
+/// connected and not empty.
+const DIAGRAMS: [&[Shape]; 4] = [
+	&[circle(200.0, 150.0, 100.0)],
+	&[circle(160.0, 150.0, 100.0), circle(240.0, 150.0, 100.0)],
+	&[
+		circle(165.0, 120.0, 90.0),
+		circle(235.0, 120.0, 90.0),
+		circle(200.0, 185.0, 90.0),
+	],
+	&[
+		Shape { cx: 150.0, cy: 170.0, rx: 150.0, ry: 70.0, angle: 45.0 },
+		Shape { cx: 180.0, cy: 130.0, rx: 150.0, ry: 70.0, angle: 45.0 },
+		Shape { cx: 220.0, cy: 130.0, rx: 150.0, ry: 70.0, angle: -45.0 },
+		Shape { cx: 250.0, cy: 170.0, rx: 150.0, ry: 70.0, angle: -45.0 },
+	],
+];
+
+impl Shape {
+	fn contains(&self, x: f64, y: f64) -> bool {
+		let (sin, cos) = self.angle.to_radians().sin_cos();
+		let (dx, dy) = (x - self.cx, y - self.cy);
+		let u = (dx * cos + dy * sin) / self.rx;
+		let v = (dy * cos - dx * sin) / self.ry;
+		u * u + v * v <= 1.0
+	}
+
+	fn svg(&self, attributes: &str) -> String {
+		format!("<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" \
+			transform=\"rotate({} {} {})\"{}/>",
+			self.cx, self.cy, self.rx, self.ry, self.angle, self.cx, self.cy,
+			attributes)
+	}
+}
+
+/// Region of the point (x, y) in a diagram.
+fn region_at(shapes: &[Shape], x: f64, y: f64) -> usize {
+	shapes.iter().enumerate()
+		.filter(|(_, shape)| shape.contains(x, y))
+		.fold(0, |mask, (index, _)| mask | 1 << index)
+}
+
+/// Where to write the name of each set: the middle of the region which is
+/// only in this set.
+fn label_positions(shapes: &[Shape]) -> Vec<(f64, f64)> {
+	let mut sums = vec![(0.0, 0.0, 0.0); shapes.len()];
+	for x in (0..WIDTH as usize).step_by(2) {
+		for y in (0..HEIGHT as usize).step_by(2) {
+			let (x, y) = (x as f64, y as f64);
+			let mask = region_at(shapes, x, y);
+			if mask.count_ones() == 1 {
+				let sum = &mut sums[mask.trailing_zeros() as usize];
+				*sum = (sum.0 + x, sum.1 + y, sum.2 + 1.0);
+			}
+		}
+	}
+	sums.into_iter().map(|(x, y, count)| (x / count, y / count)).collect()
+}
+
+impl<T: Ord + Clone> Venn<T> {
+	/// Compute the 2^n regions of the diagram of n sets. Since the
+	/// complement is taken in the union of the sets, like `eval_set`, the
+	/// region which is outside of every set has no element.
+	pub fn new(formula: &str, sets: &[Vec<T>]) -> Self {
+		let tree = BoolNode::tree(formula);
+		let variables = set_variables(formula, sets.len());
+		let varmap = variable_sets(formula, sets);
+		let regions = (0..1usize << variables.len()).map(|mask| {
+			let mut formula = String::new();
+			let mut assignment = 0u32;
+			for (index, variable) in variables.iter().enumerate() {
+				formula.push(*variable);
+				if mask >> index & 1 == 1 {
+					assignment |= 1 << (*variable as u32 - 'A' as u32);
+				} else {
+					formula.push('!');
+				}
+				if index > 0 {
+					formula.push('&');
+				}
+			}
+			if formula.is_empty() {
+				formula.push('1');
+			}
+			let elements = eval_set_tree(&BoolNode::tree(&formula), &varmap);
+			Region { mask, formula, elements, selected: tree.eval(&assignment) }
+		}).collect();
+		Venn { variables, regions }
+	}
+
+	pub fn variables(&self) -> &[char] {
+		&self.variables
+	}
+
+	/// Every region, in the order of their masks.
+	pub fn regions(&self) -> &[Region<T>] {
+		&self.regions
+	}
+
+	/// Union of the elements of the selected regions, which is the set
+	/// `eval_set_tree` gives for the formula.
+	pub fn selected_elements(&self) -> Vec<T> {
+		let mut elements: Vec<T> = self.regions.iter()
+			.filter(|region| region.selected)
+			.flat_map(|region| region.elements.iter().cloned())
+			.collect();
+		elements.sort();
+		elements
+	}
+
+	fn shapes(&self) -> &'static [Shape] {
+		match self.variables.len() {
+			1..=4 => DIAGRAMS[self.variables.len() - 1],
+			count => panic!("cannot draw a Venn diagram of {} sets (1 to 4)", count),
+		}
+	}
+
+	/// Draw the diagram in a text frame standing for the universe, the
+	/// selected regions being filled with '#' and the borders drawn with '.'.
+	pub fn ascii(&self) -> String {
+		let shapes = self.shapes();
+		let (cell_width, cell_height) = (WIDTH / COLUMNS as f64, HEIGHT / ROWS as f64);
+		let grid: Vec<Vec<usize>> = (0..ROWS).map(|row| {
+			(0..COLUMNS).map(|column| {
+				region_at(shapes, (column as f64 + 0.5) * cell_width,
+					(row as f64 + 0.5) * cell_height)
+			}).collect()
+		}).collect();
+		let mut lines: Vec<Vec<char>> = grid.iter().enumerate().map(|(row, masks)| {
+			masks.iter().enumerate().map(|(column, mask)| {
+				let border = masks.get(column + 1).is_some_and(|next| next != mask)
+					|| grid.get(row + 1).is_some_and(|next| next[column] != *mask);
+				if border {
+					'.'
+				} else if self.regions[*mask].selected {
+					'#'
+				} else {
+					' '
+				}
+			}).collect()
+		}).collect();
+		for (variable, (x, y)) in self.variables.iter().zip(label_positions(shapes)) {
+			lines[(y / cell_height) as usize][(x / cell_width) as usize] = *variable;
+		}
+		let frame = format!("+{}+\n", "-".repeat(COLUMNS));
+		let mut result = frame.clone();
+		for line in lines {
+			result.push('|');
+			result.extend(line);
+			result.push_str("|\n");
+		}
+		result.push_str(&frame);
+		result
+	}
+
+	/// Draw the diagram as an SVG image, the rectangle around the sets
+	/// standing for the universe and the selected regions being filled.
+	pub fn svg(&self) -> String {
+		let shapes = self.shapes();
+		let mut defs: Vec<String> = vec![];
+		let mut fills: Vec<String> = vec![];
+		for (index, shape) in shapes.iter().enumerate() {
+			defs.push(format!("<clipPath id=\"set-{}\">{}</clipPath>",
+				index, shape.svg("")));
+		}
+		for region in self.regions.iter().filter(|region| region.selected) {
+			let outside: Vec<String> = shapes.iter().enumerate()
+				.filter(|(index, _)| region.mask >> index & 1 == 0)
+				.map(|(_, shape)| shape.svg(" fill=\"black\""))
+				.collect();
+			defs.push(format!("<mask id=\"region-{}\">\
+				<rect width=\"{}\" height=\"{}\" fill=\"white\"/>{}</mask>",
+				region.mask, WIDTH, HEIGHT, outside.concat()));
+			let mut fill = format!("<rect width=\"{}\" height=\"{}\" \
+				fill=\"#9ecae1\" mask=\"url(#region-{})\"/>",
+				WIDTH, HEIGHT, region.mask);
+			for index in (0..shapes.len()).filter(|index| region.mask >> index & 1 == 1) {
+				fill = format!("<g clip-path=\"url(#set-{})\">{}</g>", index, fill);
+			}
+			fills.push(fill);
+		}
+		let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" \
+			width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+			WIDTH, HEIGHT, WIDTH, HEIGHT);
+		svg.push_str(&format!("<defs>{}</defs>\n", defs.concat()));
+		svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"white\" \
+			stroke=\"black\"/>\n", WIDTH, HEIGHT));
+		for fill in fills {
+			svg.push_str(&fill);
+			svg.push('\n');
+		}
+		for shape in shapes {
+			svg.push_str(&shape.svg(" fill=\"none\" stroke=\"black\""));
+			svg.push('\n');
+		}
+		for (variable, (x, y)) in self.variables.iter().zip(label_positions(shapes)) {
+			svg.push_str(&format!("<text x=\"{:.0}\" y=\"{:.0}\" \
+				text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>\n",
+				x, y, variable));
+		}
+		svg.push_str("</svg>\n");
+		svg
+	}
+}
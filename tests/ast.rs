@@ -0,0 +1,428 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+use ready_set_boole::utils::{ast::*, truth::{self, eval_formula, sat}};
+use common::truth_diff;
+
+#[test]
+fn bool_ast_tree_basics() {
+	let mut formula = "A";
+	let mut ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "AB|";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "AB&C|";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "ABC&|";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "AB=";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "AB=!";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "ABCDEFGH||=&&||!";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+
+	formula = "ABCDEFGHI>^|=&&||!";
+	ast = BoolNode::tree(formula);
+	assert_eq!(formula, &ast.to_formula());
+}
+
+#[test]
+#[should_panic(expected = "formula string is empty")]
+fn bool_ast_tree_empty_string() {
+	BoolNode::tree("");
+}
+
+#[test]
+#[should_panic(expected = "unused operands in formula string")]
+fn bool_ast_tree_unused_operands() {
+	BoolNode::tree("ABC|");
+}
+
+#[test]
+#[should_panic(expected = "missing operand for 'Negation' operation")]
+fn bool_ast_tree_missing_operand() {
+	BoolNode::tree("!");
+}
+
+#[test]
+fn bool_ast_fold() {
+	let ast = BoolNode::tree("A");
+	assert_eq!(ast.depth(), 0);
+	assert_eq!(ast.size(), 1);
+	assert_eq!(ast.variables(), vec!['A']);
+
+	let ast = BoolNode::tree("CA&B!A|>");
+	assert_eq!(ast.depth(), 3);
+	assert_eq!(ast.size(), 8);
+	assert_eq!(ast.variables(), vec!['A', 'B', 'C']);
+
+	let leaves = ast.fold(&mut |node, left: Option<u32>, right| {
+		match node.boolean_type() {
+			BoolType::Variable => 1,
+			_ => left.unwrap_or(0) + right.unwrap_or(0),
+		}
+	});
+	assert_eq!(leaves, 4);
+}
+
+#[test]
+fn bool_ast_fold_operands() {
+	let ast = BoolNode::tree("{ABC}2≤D&");
+	assert_eq!(ast.size(), 6);
+	assert_eq!(ast.variables(), vec!['A', 'B', 'C', 'D']);
+
+	let leaves = ast.fold_operands(&mut |node, children: Vec<u32>| {
+		match node.boolean_type() {
+			BoolType::Variable => 1,
+			_ => children.iter().sum(),
+		}
+	});
+	assert_eq!(leaves, 4);
+}
+
+#[test]
+fn bool_ast_fold_nary() {
+	let ast = BoolNode::nary('&', vec![BoolNode::tree("AB|"), BoolNode::variable('C'),
+		BoolNode::tree("D!")]);
+	let leaves = ast.fold(&mut |node, left: Option<u32>, right| {
+		match node.boolean_type() {
+			BoolType::Variable => 1,
+			_ => left.unwrap_or(0) + right.unwrap_or(0),
+		}
+	});
+	assert_eq!(leaves, 4);
+	let formula = ast.fold(&mut |node, left: Option<String>, right| {
+		format!("{}{}{}", left.unwrap_or_default(), right.unwrap_or_default(), node.op_symbol())
+	});
+	assert_eq!(formula, "AB|CD!&&");
+}
+
+#[test]
+#[should_panic(expected = "'AtMost' operation has no binary children to fold")]
+fn bool_ast_fold_cardinality() {
+	BoolNode::tree("{ABC}2≤D&").fold(&mut |_, _: Option<u32>, _| 0);
+}
+
+#[test]
+fn bool_ast_visitors() {
+	struct Collector { symbols: String, stop_at: char }
+	impl Visitor for Collector {
+		fn enter(&mut self, node: &BoolNode) -> Flow {
+			self.symbols.push(node.op_symbol());
+			if node.op_symbol() == self.stop_at {
+				Flow::Abort
+			} else if node.boolean_type() == BoolType::Negation {
+				Flow::SkipChildren
+			} else {
+				Flow::Continue
+			}
+		}
+	}
+
+	let ast = BoolNode::tree("AB!|C&");
+	let mut collector = Collector { symbols: String::new(), stop_at: '_' };
+	assert_eq!(ast.accept(&mut collector), Flow::Continue);
+	assert_eq!(collector.symbols, "&|A!C");
+	let mut collector = Collector { symbols: String::new(), stop_at: 'A' };
+	assert_eq!(ast.accept(&mut collector), Flow::Abort);
+	assert_eq!(collector.symbols, "&|A");
+
+	struct Rewriter { count: usize }
+	impl VisitorMut for Rewriter {
+		fn enter(&mut self, node: &mut BoolNode) -> Flow {
+			if node.boolean_type() == BoolType::MaterialCondition {
+				node.replace_material_condition();
+				self.count += 1;
+			}
+			Flow::Continue
+		}
+	}
+
+	let formula = "AB>CD>>";
+	let mut ast = BoolNode::tree(formula);
+	let mut rewriter = Rewriter { count: 0 };
+	ast.accept_mut(&mut rewriter);
+	assert_eq!(rewriter.count, 3);
+	assert_eq!(ast.to_formula(), "A!B|!C!D||");
+	truth_diff(formula, &ast.to_formula());
+}
+
+#[test]
+fn bool_ast_eval() {
+	let ast = BoolNode::tree("AB|C&");
+	assert!(ast.eval(&[false, true, true][..]));
+	assert!(!ast.eval(&vec![true, true, false]));
+	assert!(ast.eval(&0b101u32));
+	assert!(!ast.eval(&0b011u32));
+	let varmap: HashMap<char, bool> =
+		vec![('A', false), ('B', false), ('C', true)].into_iter().collect();
+	assert!(!ast.eval(&varmap));
+
+	let formula = "AB^C>D=A!|";
+	let ast = BoolNode::tree(formula);
+	for values in 0..16u32 {
+		let mut compiled = String::new();
+		for c in formula.chars() {
+			match c {
+				'A'..='Z' => {
+					compiled.push(if values.value(c).unwrap() { '1' } else { '0' });
+				},
+				_ => compiled.push(c),
+			}
+		}
+		assert_eq!(ast.eval(&values), eval_formula(&compiled));
+	}
+
+	assert!(BoolNode::tree("10&!").eval(&0u32));
+}
+
+#[test]
+#[should_panic(expected = "no value for variable 'C'")]
+fn bool_ast_eval_missing_variable() {
+	BoolNode::tree("AB|C&").eval(&[true, true][..]);
+}
+
+#[test]
+fn bool_ast_partial_eval() {
+	let ast = BoolNode::tree("AB|C&");
+	let mut varmap: HashMap<char, bool> = HashMap::new();
+	varmap.insert('C', true);
+	assert_eq!(ast.partial_eval(&varmap).to_formula(), "AB|");
+	varmap.insert('A', false);
+	assert_eq!(ast.partial_eval(&varmap).to_formula(), "B");
+	varmap.insert('C', false);
+	assert_eq!(ast.partial_eval(&varmap).to_formula(), "0");
+
+	let ast = BoolNode::tree("AB>C^D=");
+	varmap.clear();
+	varmap.insert('B', false);
+	varmap.insert('C', true);
+	let residual = ast.partial_eval(&varmap);
+	assert_eq!(residual.to_formula(), "AD=");
+	for values in 0..16u32 {
+		let values = values & !0b110 | 0b100;
+		assert_eq!(ast.eval(&values), residual.eval(&values));
+	}
+
+	assert_eq!(BoolNode::tree("A1&0|B>").simplify().to_formula(), "AB>");
+	assert_eq!(BoolNode::tree("A1^1^").simplify().to_formula(), "A");
+	assert_eq!(BoolNode::tree("A1&0|B>").to_formula(), "A1&0|B>");
+	assert_eq!(BoolNode::tree("A0&B=").simplify().to_formula(), "B!");
+}
+
+#[test]
+fn bool_ast_substitution() {
+	let ast = BoolNode::tree("AB&A|");
+	let result = ast.substitute('A', &BoolNode::tree("CA>"));
+	assert_eq!(result.to_formula(), "CA>B&CA>|");
+	let result = ast.substitute('Z', &BoolNode::tree("CA>"));
+	assert_eq!(result.to_formula(), "AB&A|");
+
+	let mut substitutions: HashMap<char, BoolNode> = HashMap::new();
+	substitutions.insert('A', BoolNode::tree("B!"));
+	substitutions.insert('B', BoolNode::tree("AC^"));
+	let result = ast.substitute_all(&substitutions);
+	assert_eq!(result.to_formula(), "B!AC^&B!|");
+
+	let mut renaming: HashMap<char, char> = HashMap::new();
+	renaming.insert('A', 'B');
+	renaming.insert('B', 'A');
+	assert_eq!(ast.rename(&renaming).to_formula(), "BA&B|");
+	renaming.insert('C', 'D');
+	assert_eq!(BoolNode::tree("AB>C|").rename(&renaming).to_formula(), "BA>D|");
+}
+
+#[test]
+fn bool_ast_compose() {
+	let template = BoolNode::tree("XY>");
+	let mut parts: HashMap<char, BoolNode> = HashMap::new();
+	parts.insert('X', BoolNode::tree("AB|"));
+	parts.insert('Y', BoolNode::tree("CA="));
+	let mut result = BoolNode::compose(&template, &parts);
+	assert_eq!(result.to_formula(), "AB|CA=>");
+	let orig = result.clone();
+	result.to_cnf();
+	assert!(result.conjunctive_normal_form());
+	assert_eq!(orig, result);
+}
+
+#[test]
+#[should_panic(expected = "no formula given for template variable 'Y'")]
+fn bool_ast_compose_missing_part() {
+	let mut parts: HashMap<char, BoolNode> = HashMap::new();
+	parts.insert('X', BoolNode::tree("AB|"));
+	BoolNode::compose(&BoolNode::tree("XY>"), &parts);
+}
+
+#[test]
+fn bool_ast_quantifiers() {
+	let formula = "AB&A∃C|";
+	let ast = BoolNode::tree(formula);
+	assert_eq!(ast.to_formula(), formula);
+	assert_eq!(ast.to_infix(), "(∃A. (A ∧ B)) ∨ C");
+	assert_eq!(ast.variables(), vec!['A', 'B', 'C']);
+	assert_eq!(ast.free_variables(), vec!['B', 'C']);
+	assert!(ast.is_quantified());
+	assert!(!ast.is_prenex());
+
+	let mut ast = BoolNode::tree(formula);
+	ast.eliminate_quantifiers();
+	assert_eq!(ast.to_formula(), "BC|");
+
+	let mut ast = BoolNode::tree("AB|A∀");
+	ast.eliminate_quantifiers();
+	assert_eq!(ast.to_formula(), "B");
+
+	let mut ast = BoolNode::tree("AA!|A∀");
+	ast.eliminate_quantifiers();
+	assert_eq!(ast.to_formula(), "1");
+
+	let mut ast = BoolNode::tree("AB^C&B∃A∀");
+	ast.eliminate_quantifiers();
+	assert_eq!(ast.to_formula(), "C");
+}
+
+#[test]
+#[should_panic(expected = "operand bound by 'Existential' operation is not a variable")]
+fn bool_ast_quantifier_without_variable() {
+	BoolNode::tree("AB&B!∃");
+}
+
+#[test]
+fn bool_ast_eval_qbf() {
+	assert!(BoolNode::tree("AB=B∃A∀").eval_qbf(&0u32));
+	assert!(BoolNode::tree("AB=A∃B∀").eval_qbf(&0u32));
+	assert!(!BoolNode::tree("AB=A∀B∃").eval_qbf(&0u32));
+	assert!(BoolNode::tree("AC&B|A∃").eval_qbf(&0b100u32));
+	assert!(!BoolNode::tree("AC&B|A∀").eval_qbf(&0b100u32));
+
+	let ast = BoolNode::tree("AB&A∃C|");
+	let mut copy = ast.clone();
+	copy.eliminate_quantifiers();
+	for values in 0..8u32 {
+		assert_eq!(ast.eval(&values), copy.eval(&values));
+	}
+	assert_eq!(ast, BoolNode::tree("BC|"));
+}
+
+#[test]
+fn bool_ast_quantified_substitution() {
+	let ast = BoolNode::tree("AB&B∃");
+	let result = ast.substitute('A', &BoolNode::tree("B"));
+	assert_eq!(result.to_formula(), "BC&C∃");
+	let result = ast.substitute('B', &BoolNode::tree("A"));
+	assert_eq!(result.to_formula(), "AB&B∃");
+}
+
+#[test]
+fn quantified_truth_table_and_sat() {
+	let expected: &str = "\
+		| B | C | = |\n\
+		|---|---|---|\n\
+		| 0 | 0 | 0 |\n\
+		| 0 | 1 | 1 |\n\
+		| 1 | 0 | 1 |\n\
+		| 1 | 1 | 1 |\n\
+	";
+	let table = truth::table::<std::io::Stdout>("AB&A∃C|", None);
+	assert_eq!(table.unwrap(), expected);
+
+	assert!(!sat("AA!&A∃"));
+	assert!(!sat("AB&A∀"));
+	assert!(sat("AB|A∀"));
+	assert!(sat("AB=B∃A∀"));
+
+	let mut ast = BoolNode::tree("AB>C&A∃");
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!(ast, BoolNode::tree("C"));
+}
+
+#[test]
+fn cardinality_nodes() {
+	let formula = "{ABC}2≤";
+	let ast = BoolNode::tree(formula);
+	assert_eq!(ast.to_formula(), formula);
+	assert_eq!(ast.to_infix(), "#{A, B, C} ≤ 2");
+	assert_eq!(ast.operands().len(), 3);
+	assert_eq!(ast.bound(), 2);
+	assert!(ast.eval(&0b011u32));
+	assert!(!ast.eval(&0b111u32));
+
+	let formula = "{AB&C!{DE}1#}12≥D|";
+	let ast = BoolNode::tree(formula);
+	assert_eq!(ast.to_formula(), formula);
+	assert_eq!(ast.to_infix(), "(#{A ∧ B, ¬C, #{D, E} = 1} ≥ 12) ∨ D");
+	assert_eq!(ast.variables(), vec!['A', 'B', 'C', 'D', 'E']);
+
+	let ast = BoolNode::at_least(2, vec![BoolNode::tree("A"),
+		BoolNode::tree("1"), BoolNode::tree("C")]);
+	assert_eq!(ast.to_formula(), "{A1C}2≥");
+	assert_eq!(ast.simplify().to_formula(), "{AC}1≥");
+	let mut varmap: HashMap<char, bool> = HashMap::new();
+	varmap.insert('A', false);
+	assert_eq!(ast.partial_eval(&varmap).to_formula(), "{C}1≥");
+	varmap.insert('C', true);
+	assert_eq!(ast.partial_eval(&varmap).to_formula(), "1");
+}
+
+#[test]
+#[should_panic(expected = "missing '{' for 'AtMost' operation")]
+fn cardinality_missing_brace() {
+	BoolNode::tree("AB}1≤");
+}
+
+#[test]
+fn canonical_forms() {
+	fn canonical(formula: &str) -> String {
+		Canonical::new(&BoolNode::tree(formula)).formula().to_formula()
+	}
+
+	assert_eq!(canonical("BA&"), "AB&");
+	assert_eq!(canonical("CA&B&"), "ABC&&");
+	assert_eq!(canonical("AB|C&"), "CAB|&");
+	assert_eq!(canonical("CBA|&"), "CAB|&");
+	assert_eq!(canonical("BA>C!!^"), "C!!BA>^");
+	assert_eq!(canonical("DC=BA==A∃"), "ABCD===A∃");
+	assert_eq!(canonical("{CB!A}2≤"), "{ACB!}2≤");
+	assert_eq!(canonical("AB&C|"), "CAB&|");
+	assert_eq!(canonical("CAB&|"), "CAB&|");
+
+	let mut ast = BoolNode::tree("DB&CA&&");
+	ast.canonicalize();
+	assert_eq!(ast.to_formula(), "ABCD&&&");
+	let ordering = BoolNode::tree("A").structural_cmp(&BoolNode::tree("B"));
+	assert_eq!(ordering, std::cmp::Ordering::Less);
+	let ordering = BoolNode::tree("AB&").structural_cmp(&BoolNode::tree("A"));
+	assert_eq!(ordering, std::cmp::Ordering::Greater);
+
+	let formulas = ["AB&", "BA&", "AB|", "BA|", "CAB&&", "BCA&&", "ABC&&", "AB&C&",
+		"AB>", "BA>", "1A&", "A1&"];
+	let set: HashSet<Canonical> = formulas.iter()
+		.map(|formula| Canonical::from(BoolNode::tree(formula)))
+		.collect();
+	assert_eq!(set.len(), 6);
+	assert!(set.contains(&Canonical::new(&BoolNode::tree("BCA&&"))));
+	// equivalent but not structurally equal
+	assert!(!set.contains(&Canonical::new(&BoolNode::tree("A!B!|!"))));
+
+	// too many variables for the truth table comparison
+	let letters: Vec<char> = ('A'..='Z').collect();
+	let ast = BoolNode::conjunction(letters.iter().map(|c| BoolNode::variable(*c)).collect());
+	let reversed = BoolNode::conjunction(letters.iter().rev()
+		.map(|c| BoolNode::variable(*c)).collect());
+	assert_eq!(ast, reversed);
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "cli")]
+
+use ready_set_boole::cli;
+use ready_set_boole::utils::{ast::BoolNode, truth};
+
+fn cli_run(args: &[&str], input: &str) -> (i32, String) {
+	let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+	let mut output: Vec<u8> = vec![];
+	let code = cli::run(&args, input.as_bytes(), &mut output);
+	(code, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn cli_formula_commands() {
+	assert_eq!(cli_run(&["eval", "10|", "10&"], ""),
+		(cli::EXIT_SUCCESS, String::from("true\nfalse\n")));
+	assert_eq!(cli_run(&["sat", "AA!&"], ""),
+		(cli::EXIT_SUCCESS, String::from("false\n")));
+	assert_eq!(cli_run(&["cnf", "AB&C|"], ""),
+		(cli::EXIT_SUCCESS, String::from("AC|BC|&\n")));
+	assert_eq!(cli_run(&["-f", "infix", "dnf", "AB|C&"], ""),
+		(cli::EXIT_SUCCESS, String::from("(A ∧ C) ∨ (B ∧ C)\n")));
+	assert_eq!(cli_run(&["nnf", "--format", "tree", "AB&!"], ""),
+		(cli::EXIT_SUCCESS, format!("{}\n", BoolNode::tree("A!B!|"))));
+	assert_eq!(cli_run(&["tree", "AB>"], ""),
+		(cli::EXIT_SUCCESS, format!("{}\n", BoolNode::tree("AB>"))));
+	let table = truth::table::<Vec<u8>>("AB^", None).unwrap();
+	assert_eq!(cli_run(&["table"], "AB^\n"), (cli::EXIT_SUCCESS, table));
+}
+
+#[test]
+fn cli_other_commands() {
+	assert_eq!(cli_run(&["-s", "0,1,2", "--set", "{1, 3}", "set", "AB&", "AB^"], ""),
+		(cli::EXIT_SUCCESS, String::from("{1}\n{0, 2, 3}\n")));
+	assert_eq!(cli_run(&["powerset", "1,2"], ""),
+		(cli::EXIT_SUCCESS, String::from("{}\n{1}\n{2}\n{1, 2}\n")));
+	assert_eq!(cli_run(&["gray"], "0\n1\n2\n3\n"),
+		(cli::EXIT_SUCCESS, String::from("0\n1\n3\n2\n")));
+	let (code, mapped) = cli_run(&["map", "42,1337"], "");
+	assert_eq!(code, cli::EXIT_SUCCESS);
+	assert_eq!(cli_run(&["unmap"], &mapped),
+		(cli::EXIT_SUCCESS, String::from("42,1337\n")));
+}
+
+#[test]
+fn cli_errors() {
+	assert_eq!(cli_run(&["nnf"], "AB&\nA&\n\nAB|\n"),
+		(cli::EXIT_FAILURE, String::from("AB&\nAB|\n")));
+	assert_eq!(cli_run(&["gray", "-1"], "").0, cli::EXIT_FAILURE);
+	assert_eq!(cli_run(&["map", "1"], "").0, cli::EXIT_FAILURE);
+	assert_eq!(cli_run(&["unmap", "2.0"], "").0, cli::EXIT_FAILURE);
+	assert_eq!(cli_run(&["-s", "1,a", "set", "A"], "").0, cli::EXIT_USAGE);
+	assert_eq!(cli_run(&["-f", "latex", "cnf", "AB&"], "").0, cli::EXIT_USAGE);
+	assert_eq!(cli_run(&["frobnicate"], "").0, cli::EXIT_USAGE);
+	assert_eq!(cli_run(&[], "").0, cli::EXIT_USAGE);
+	let (code, help) = cli_run(&["cnf", "--help"], "");
+	assert_eq!((code, help.as_str()), (cli::EXIT_SUCCESS, cli::USAGE));
+}
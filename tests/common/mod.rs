@@ -0,0 +1,13 @@
+use ready_set_boole::utils::truth;
+
+pub fn truth_diff(left: &str, right: &str) {
+	match (truth::table::<std::io::Stdout>(left, None),
+		truth::table::<std::io::Stdout>(right, None)) {
+		(Some(left_truth), Some(right_truth)) => {
+			println!("left:\t\t'{}'\n{}", left, left_truth);
+			println!("right:\t'{}'\n{}", right, right_truth);
+			assert_eq!(left_truth, right_truth);
+		},
+		_ => panic!("missing truth"),
+	}
+}
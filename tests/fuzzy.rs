@@ -0,0 +1,146 @@
+use ready_set_boole::utils::fuzzy::*;
+use ready_set_boole::utils::truth::eval_formula;
+
+const T: Trilean = Trilean::True;
+const U: Trilean = Trilean::Unknown;
+const F: Trilean = Trilean::False;
+
+fn assert_close(result: f64, expected: f64) {
+	assert!((result - expected).abs() < 1e-9, "{} != {}", result, expected);
+}
+
+#[test]
+fn kleene_eval() {
+	assert_eq!(eval_many_valued(&Kleene, "A!", &[U]), U);
+	assert_eq!(eval_many_valued(&Kleene, "A0&", &[U]), F);
+	assert_eq!(eval_many_valued(&Kleene, "A1&", &[U]), U);
+	assert_eq!(eval_many_valued(&Kleene, "A1|", &[U]), T);
+	assert_eq!(eval_many_valued(&Kleene, "A0|", &[U]), U);
+	assert_eq!(eval_many_valued(&Kleene, "0A>", &[U]), T);
+	assert_eq!(eval_many_valued(&Kleene, "A1>", &[U]), T);
+	assert_eq!(eval_many_valued(&Kleene, "1A>", &[U]), U);
+	assert_eq!(eval_many_valued(&Kleene, "AA=", &[U]), U);
+	assert_eq!(eval_many_valued(&Kleene, "AB^", &[U, T]), U);
+	// the excluded middle does not hold for unknown values
+	assert_eq!(eval_many_valued(&Kleene, "AA!|", &[U]), U);
+	assert_eq!(eval_many_valued(&Kleene, "AB&C|", &[T, U, T]), T);
+	assert_eq!(eval_many_valued(&Kleene, "BA&C|", &[F, U, F]), F);
+	assert_eq!(eval_many_valued(&Kleene, "AB&C|", &[T, U, F]), U);
+	assert_eq!(Trilean::from(true), T);
+	assert_eq!(format!("{}{}{}", F, U, T), "0?1");
+}
+
+#[test]
+fn many_valued_extend_boolean() {
+	let formulas = ["10&", "10|", "11>", "10=", "1011||=", "01^!", "110&>0=",
+		"1!0!|1&", "10>1^"];
+	for formula in formulas {
+		let expected = eval_formula(formula);
+		assert_eq!(eval_many_valued(&Kleene, formula, &[]), Trilean::from(expected));
+		for t_norm in [TNorm::Minimum, TNorm::Product, TNorm::Lukasiewicz] {
+			let degree = eval_many_valued(&t_norm, formula, &[]);
+			assert_eq!(degree, if expected { 1.0 } else { 0.0 }, "{:?} {}",
+				t_norm, formula);
+		}
+	}
+}
+
+#[test]
+fn fuzzy_eval() {
+	let values = [0.7, 0.4];
+	assert_close(eval_many_valued(&TNorm::Minimum, "AB&", &values), 0.4);
+	assert_close(eval_many_valued(&TNorm::Minimum, "AB|", &values), 0.7);
+	assert_close(eval_many_valued(&TNorm::Minimum, "AB>", &values), 0.4);
+	assert_close(eval_many_valued(&TNorm::Minimum, "BA>", &values), 1.0);
+	assert_close(eval_many_valued(&TNorm::Minimum, "AB=", &values), 0.4);
+	assert_close(eval_many_valued(&TNorm::Minimum, "AB^", &values), 0.6);
+	assert_close(eval_many_valued(&TNorm::Product, "AB&", &values), 0.28);
+	assert_close(eval_many_valued(&TNorm::Product, "AB|", &values), 0.82);
+	assert_close(eval_many_valued(&TNorm::Product, "AB>", &values), 0.4 / 0.7);
+	assert_close(eval_many_valued(&TNorm::Product, "AB=", &values), 0.4 / 0.7);
+	assert_close(eval_many_valued(&TNorm::Lukasiewicz, "AB&", &values), 0.1);
+	assert_close(eval_many_valued(&TNorm::Lukasiewicz, "AB|", &values), 1.0);
+	assert_close(eval_many_valued(&TNorm::Lukasiewicz, "AB>", &values), 0.7);
+	assert_close(eval_many_valued(&TNorm::Lukasiewicz, "AB^", &values), 0.3);
+	assert_close(eval_many_valued(&TNorm::Lukasiewicz, "A!", &values[..1]), 0.3);
+	// the excluded middle only holds with the Łukasiewicz t-conorm
+	assert_close(eval_many_valued(&TNorm::Minimum, "AA!|", &[0.5]), 0.5);
+	assert_close(eval_many_valued(&TNorm::Product, "AA!|", &[0.5]), 0.75);
+	assert_close(eval_many_valued(&TNorm::Lukasiewicz, "AA!|", &[0.5]), 1.0);
+	for t_norm in [TNorm::Minimum, TNorm::Product, TNorm::Lukasiewicz] {
+		for (a, b) in [(0.0, 0.3), (0.2, 0.9), (0.6, 0.6), (1.0, 0.5)] {
+			// De Morgan duality of the t-norm and its t-conorm
+			assert_close(eval_many_valued(&t_norm, "AB|", &[a, b]),
+				eval_many_valued(&t_norm, "A!B!&!", &[a, b]));
+			// residuation: T(a, b) <= c if and only if a <= I(b, c)
+			let c = 0.35;
+			assert_eq!(eval_many_valued(&t_norm, "AB&", &[a, b]) <= c + 1e-12,
+				a <= eval_many_valued(&t_norm, "AB>", &[b, c]) + 1e-12);
+		}
+	}
+}
+
+#[test]
+#[should_panic(expected = "1.5 is not a valid truth value")]
+fn fuzzy_eval_out_of_range() {
+	eval_many_valued(&TNorm::Minimum, "AB&", &[0.5, 1.5]);
+}
+
+#[test]
+#[should_panic(expected = "missing values for 'AB|' formula")]
+fn many_valued_missing_values() {
+	eval_many_valued(&Kleene, "AB|", &[U]);
+}
+
+#[test]
+#[should_panic(expected = "'&' is not a valid op or is missing an argument")]
+fn many_valued_missing_argument() {
+	eval_many_valued(&Kleene, "A&", &[U]);
+}
+
+#[test]
+fn fuzzy_sets() {
+	let tall = vec![("ann", 0.9), ("bob", 0.4), ("eve", 0.1)];
+	let fast = vec![("bob", 0.8), ("eve", 0.5), ("joe", 1.0)];
+	let sets = [tall, fast];
+	let result = eval_fuzzy_set("AB&", &sets, TNorm::Minimum);
+	assert_eq!(result, vec![("bob", 0.4), ("eve", 0.1)]);
+	let result = eval_fuzzy_set("AB|", &sets, TNorm::Minimum);
+	assert_eq!(result, vec![("ann", 0.9), ("bob", 0.8), ("eve", 0.5), ("joe", 1.0)]);
+	let result = eval_fuzzy_set("AB&", &sets, TNorm::Product);
+	assert_eq!(result.len(), 2);
+	assert_close(result[0].1, 0.32);
+	assert_close(result[1].1, 0.05);
+	let result = eval_fuzzy_set("AB&", &sets, TNorm::Lukasiewicz);
+	assert_eq!(result.len(), 1);
+	assert_eq!(result[0].0, "bob");
+	assert_close(result[0].1, 0.2);
+	let result = eval_fuzzy_set("A!", &sets[..1], TNorm::Minimum);
+	assert_eq!(result.len(), 3);
+	assert_close(result[0].1, 0.1);
+	assert_close(result[1].1, 0.6);
+	assert_close(result[2].1, 0.9);
+
+	// crisp sets give the same elements as eval_set
+	let crisp = vec![vec![(0, 1.0), (1, 1.0), (2, 1.0)], vec![(0, 1.0), (3, 1.0)]];
+	let result: Vec<i32> = eval_fuzzy_set("AB^", &crisp, TNorm::Product).into_iter()
+		.map(|(element, _)| element)
+		.collect();
+	assert_eq!(result, vec![1, 2, 3]);
+}
+
+#[test]
+fn fuzzy_sets_duplicates() {
+	let tall = vec![("eve", 0.1), ("ann", 0.9), ("eve", 0.6), ("eve", 0.3)];
+	let fast = vec![("eve", 0.5), ("ann", 0.2)];
+	let result = eval_fuzzy_set("AB&", &[tall.clone(), fast], TNorm::Minimum);
+	assert_eq!(result, vec![("ann", 0.2), ("eve", 0.5)]);
+	let result = eval_fuzzy_set("A", &[tall], TNorm::Minimum);
+	assert_eq!(result, vec![("ann", 0.9), ("eve", 0.6)]);
+}
+
+#[test]
+#[should_panic(expected = "missing values for 'AB|' formula")]
+fn fuzzy_sets_missing_set() {
+	eval_fuzzy_set::<i32>("AB|", &[vec![]], TNorm::Minimum);
+}
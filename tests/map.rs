@@ -0,0 +1,84 @@
+use ready_set_boole::{map, reverse_map};
+
+#[test]
+fn map_is_in_range() {
+	let (x, y) = (0, 0);
+	let result = map(x, y);
+	assert_eq!(result, 0.0);
+
+	let (x, y) = (0, 1);
+	let result = map(x, y);
+	assert!(result != 0.0);
+
+	for coordinates in (0..=u32::MAX).step_by(4242) {
+		let x = (coordinates & 0xffff) as u16;
+		let y = ((coordinates & 0xffff0000) >> 16) as u16;
+		let result = map(x, y);
+		assert!(result.is_sign_positive());
+		assert!(result.is_nan() == false);
+		assert!(result <= 1.0);
+	}
+}
+
+#[test]
+fn map_to_reverse_map() {
+	for coordinates in (0..=u32::MAX).step_by(4444) {
+		let x_in = (coordinates & 0xffff) as u16;
+		let y_in = ((coordinates & 0xffff0000) >> 16) as u16;
+		let result = map(x_in, y_in);
+		let (x_out, y_out) = reverse_map(result);
+		assert_eq!(x_out, x_in);
+		assert_eq!(y_out, y_in);
+	}
+}
+
+#[test]
+fn reverse_map_to_map() {
+	let n_in = 0.0;
+	let (x, y) = reverse_map(n_in);
+	let n_out = map(x, y);
+	assert_eq!(n_out, n_in);
+
+	let n_in = 0.25;
+	let (x, y) = reverse_map(n_in);
+	let n_out = map(x, y);
+	assert_eq!(n_out, n_in);
+
+	let n_in = 0.5;
+	let (x, y) = reverse_map(n_in);
+	let n_out = map(x, y);
+	assert_eq!(n_out, n_in);
+
+	let n_in = 0.75;
+	let (x, y) = reverse_map(n_in);
+	let n_out = map(x, y);
+	assert_eq!(n_out, n_in);
+
+	let n_in = 1.0;
+	let (x, y) = reverse_map(n_in);
+	let n_out = map(x, y);
+	assert_eq!(n_out, n_in);
+
+	let n_in = f64::EPSILON;
+	let (x, y) = reverse_map(n_in);
+	let n_out = map(x, y);
+	assert_eq!(n_out, n_in);
+}
+
+#[test]
+#[should_panic(expected = "input number '2' is out of range [0;1]")]
+fn reverse_map_too_big() {
+	reverse_map(2.0);
+}
+
+#[test]
+#[should_panic(expected = "input number 'NaN' is out of range [0;1]")]
+fn reverse_map_nan() {
+	reverse_map(f64::NAN);
+}
+
+#[test]
+#[should_panic(expected = "input number '-42' is out of range [0;1]")]
+fn reverse_map_negative() {
+	reverse_map(-42.0);
+}
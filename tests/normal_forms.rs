@@ -0,0 +1,569 @@
+mod common;
+
+use std::time::Duration;
+use ready_set_boole::{negation_normal_form, conjunctive_normal_form};
+use ready_set_boole::utils::ast::*;
+use common::truth_diff;
+
+#[test]
+fn bool_ast_replace_exclusive_disjunction() {
+	let mut formula = "PQ^";
+	let mut expected = "PQ|PQ&!&";
+	let mut ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_exclusive_disjunction);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "PQ^A|";
+	expected = "PQ|PQ&!&A|";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_exclusive_disjunction);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "ABCDEFGHI>^|=^^||!";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_exclusive_disjunction);
+	truth_diff(formula, &ast.to_formula());
+
+	formula = "ABCDEFGHI>^^=^^^^!";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_exclusive_disjunction);
+	truth_diff(formula, &ast.to_formula());
+}
+
+#[test]
+fn bool_ast_replace_material_condition() {
+	let mut formula = "AB>";
+	let mut expected = "A!B|";
+	let mut ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_material_condition);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "ABC|>";
+	expected = "A!BC||";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_material_condition);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "AB^CD|>";
+	expected = "AB^!CD||";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_material_condition);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "ABCDEFGHI>>^=>>||!";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_material_condition);
+	truth_diff(formula, &ast.to_formula());
+}
+
+#[test]
+fn bool_ast_replace_logical_equivalence() {
+	let mut formula = "AB=";
+	let mut expected = "AB>BA>&";
+	let mut ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_logical_equivalence);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "ABC|=";
+	expected = "ABC|>BC|A>&";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_logical_equivalence);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "AB^CD|=";
+	expected = "AB^CD|>CD|AB^>&";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_logical_equivalence);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "ABCDEFGHI>==^====!";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_material_condition);
+	truth_diff(formula, &ast.to_formula());
+}
+
+#[test]
+fn bool_ast_eliminate_double_negation() {
+	let mut formula = "A!!";
+	let mut expected = "A";
+	let mut ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::eliminate_double_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "A!!!!!B|";
+	expected = "A!B|";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::eliminate_double_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "A!!!!!!B!!|";
+	expected = "AB|";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::eliminate_double_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "AB|!!!!!!!CD!E|^!!|";
+	expected = "AB|!CD!E|^|";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::eliminate_double_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+}
+
+#[test]
+fn bool_ast_replace_junction_negation() {
+	let mut formula = "AB&!";
+	let mut expected = "A!B!|";
+	let mut ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_junction_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "AB|!";
+	expected = "A!B!&";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_junction_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "AB|!!";
+	expected = "A!B!&!";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_junction_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+
+	formula = "AB&CD^|!";
+	expected = "A!B!|CD^!&";
+	ast = BoolNode::tree(formula);
+	ast.pre_order(BoolNode::replace_junction_negation);
+	assert_eq!(ast.to_formula(), expected);
+	truth_diff(formula, expected);
+}
+
+#[test]
+fn nnf_subject_tests() {
+	let mut formula = "AB&!";
+	let mut ast = BoolNode::tree(formula);
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	assert_eq!("A!B!|", negation_normal_form(formula));
+
+	formula = "AB|!";
+	ast = BoolNode::tree(formula);
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	assert_eq!("A!B!&", negation_normal_form(formula));
+
+	formula = "AB>";
+	ast = BoolNode::tree(formula);
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	assert_eq!("A!B|", negation_normal_form(formula));
+
+	formula = "AB=";
+	ast = BoolNode::tree(formula);
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	//assert_eq!("AB&A!B!&|", negation_normal_form("AB="));
+	truth_diff("AB&A!B!&|", &negation_normal_form(formula));
+
+	formula = "AB|C&!";
+	ast = BoolNode::tree(formula);
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	assert_eq!("A!B!&C!|", negation_normal_form(formula));
+}
+
+#[test]
+fn nnf_harder_tests() {
+	let formula = "AB&!CD|!EF>!GH^!&!&!&!";
+	let mut ast = BoolNode::tree(formula);
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	truth_diff(formula, &negation_normal_form(formula));
+}
+
+#[test]
+fn rotations() {
+	let mut formula = "AB|C|";
+	let mut ast = BoolNode::tree(formula);
+	ast.right_rotate(BoolType::Disjunction);
+	assert_eq!("ABC||", ast.to_formula());
+	ast.left_rotate(BoolType::Disjunction);
+	assert_eq!(formula, ast.to_formula());
+
+	formula = "AB&C&";
+	ast = BoolNode::tree(formula);
+	ast.right_rotate(BoolType::Conjunction);
+	assert_eq!("ABC&&", ast.to_formula());
+	ast.left_rotate(BoolType::Conjunction);
+	assert_eq!(formula, ast.to_formula());
+
+	formula = "AB&C|";
+	ast = BoolNode::tree(formula);
+	ast.right_rotate(BoolType::Disjunction);
+	assert_eq!(formula, ast.to_formula());
+	ast.left_rotate(BoolType::Disjunction);
+	assert_eq!(formula, ast.to_formula());
+}
+
+#[test]
+fn distribute() {
+	let formula = "ABC&|";
+	let mut ast = BoolNode::tree(formula);
+	ast.distribute(BoolType::Disjunction);
+	assert_eq!("AB|AC|&", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "AD&C|";
+	ast = BoolNode::tree(formula);
+	ast.distribute(BoolType::Disjunction);
+	assert_eq!("AC|DC|&", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "ABC|&";
+	ast = BoolNode::tree(formula);
+	ast.distribute(BoolType::Conjunction);
+	assert_eq!("AB&AC&|", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "AD|C&";
+	ast = BoolNode::tree(formula);
+	ast.distribute(BoolType::Conjunction);
+	assert_eq!("AC&DC&|", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+}
+
+#[test]
+fn factor() {
+	let formula = "AB|AC|&";
+	let mut ast = BoolNode::tree(formula);
+	ast.factor(BoolType::Disjunction);
+	assert_eq!("ABC&|", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "AB&AC&|";
+	ast = BoolNode::tree(formula);
+	ast.factor(BoolType::Conjunction);
+	assert_eq!("ABC|&", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "AB|CA|&";
+	ast = BoolNode::tree(formula);
+	ast.factor(BoolType::Disjunction);
+	assert_eq!("ABC&|", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "BA|AC|&";
+	ast = BoolNode::tree(formula);
+	ast.factor(BoolType::Disjunction);
+	assert_eq!("ABC&|", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+
+	let formula = "BA|CA|&";
+	ast = BoolNode::tree(formula);
+	ast.factor(BoolType::Disjunction);
+	assert_eq!("ABC&|", ast.to_formula());
+	truth_diff(formula, &ast.to_formula());
+}
+
+#[test]
+fn cnf_subject_tests() {
+	let mut formula = "AB&!";
+	let mut ast = BoolNode::tree(formula);
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!("A!B!|", conjunctive_normal_form(formula));
+
+	formula = "AB|!";
+	ast = BoolNode::tree(formula);
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!("A!B!&", conjunctive_normal_form(formula));
+
+	formula = "AB|C&";
+	ast = BoolNode::tree(formula);
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!("AB|C&", conjunctive_normal_form(formula));
+
+	formula = "AB|C|D|";
+	ast = BoolNode::tree(formula);
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!("ABCD|||", conjunctive_normal_form(formula));
+
+	formula = "AB&C&D&";
+	ast = BoolNode::tree(formula);
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!("ABCD&&&", conjunctive_normal_form(formula));
+
+	formula = "ABCD&|&";
+	ast = BoolNode::tree(formula);
+	ast.to_cnf();
+	assert!(ast.conjunctive_normal_form());
+	assert_eq!("ABC|BD|&&", conjunctive_normal_form(formula));
+}
+
+#[test]
+fn cnf_harder_tests() {
+	fn cnf_hard_test(formula: &str) {
+		let orig = BoolNode::tree(formula);
+		let mut mine = orig.clone();
+		mine.to_cnf();
+		assert_eq!(orig, mine);
+		println!("orig: '{}'\n{}", orig.to_formula(), orig);
+		println!("mine: '{}'\n{}\n", mine.to_formula(), mine);
+		assert!(mine.conjunctive_normal_form());
+	}
+
+	cnf_hard_test("AB&CD||");
+	cnf_hard_test("DEFGH|IJKL|&|&|&|");
+
+	cnf_hard_test("GH|IJKL|&|&");
+	cnf_hard_test("FGH|IJKL|&|&|");
+	cnf_hard_test("EFGH|IJKL|&|&|&");
+	cnf_hard_test("CDEFGH|IJKL|&|&|&|&");
+	cnf_hard_test("ABCDEFGH|IJKL|&|&|&|&|&");
+
+	cnf_hard_test("ABCDEFG!H|IJKL|&|&|&|&|&");
+	cnf_hard_test("ABCDEFGH>IJKL|&|&|&|&|&");
+
+	cnf_hard_test("ABCDE&||&");
+	cnf_hard_test("AB&C|D|EF&&");
+	cnf_hard_test("BF|DF|&G!H||BDFIJ||||BDFI|||KL||&&");
+	cnf_hard_test("ABC|BD|E|BD&F|G!H||BDF||I|J|BDFI|||KL||&&&&&");
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI|||KL||&&");
+
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI||&KL||&&");
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI|&|KL||&&");
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI&||KL||&&");
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI&&|KL||&&");
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI&&&KL||&&");
+	cnf_hard_test("BF&DF&&G!H&&BDFIJ&&&&BDFI&&&KL&|&&");
+}
+
+#[test]
+fn dnf_tests() {
+	fn dnf_test(formula: &str) {
+		let orig = BoolNode::tree(formula);
+		let mut mine = orig.clone();
+		mine.to_dnf();
+		assert_eq!(orig, mine);
+		assert!(mine.disjunctive_normal_form());
+	}
+
+	let mut ast = BoolNode::tree("AB|C&");
+	ast.to_dnf();
+	assert_eq!(ast.to_formula(), "AC&BC&|");
+	assert!(!BoolNode::tree("AB|C&").disjunctive_normal_form());
+	assert!(!BoolNode::tree("AB&C|").conjunctive_normal_form());
+	assert!(BoolNode::tree("AB&C|").disjunctive_normal_form());
+
+	dnf_test("AB&CD||");
+	dnf_test("AB|CD|&");
+	dnf_test("AB=C>");
+	dnf_test("ABCDE|||&");
+	dnf_test("AB|CD|EF|&&");
+	dnf_test("AB^C!D>&!");
+	dnf_test("BF|DF|&G!H||BDFIJ||||BDFI|||KL||&&");
+}
+
+#[test]
+fn cardinality_to_cnf() {
+	for formula in ["{ABCD}2≤", "{ABCD}2≥", "{ABC}1#", "{ABC}0≥",
+		"{AB}3≤", "{AB}3#", "{AB|C!D}1≤E&"].iter() {
+		let orig = BoolNode::tree(formula);
+		let mut mine = orig.clone();
+		mine.to_cnf();
+		assert!(mine.conjunctive_normal_form());
+		assert_eq!(orig, mine);
+	}
+}
+
+#[test]
+fn traced_conversions() {
+	let mut ast = BoolNode::tree("AB>!C=");
+	let trace = ast.to_nnf_traced();
+	assert_eq!(trace.initial, "AB>!C=");
+	assert_eq!(trace.steps.len(), 7);
+	assert_eq!(trace.steps[0], Step {
+		rule: "replace_logical_equivalence",
+		path: String::new(),
+		before: String::from("AB>!C="),
+		after: String::from("AB>!C>CAB>!>&"),
+	});
+	assert_eq!(trace.steps[2].rule, "replace_material_condition");
+	assert_eq!(trace.steps[2].path, "llll");
+	assert_eq!((trace.steps[6].before.as_str(), trace.steps[6].after.as_str()),
+		("A!B|!", "AB!&"));
+	assert_eq!(trace.formulas().last().unwrap().to_formula(), ast.to_formula());
+	let derivation = trace.derivation();
+	assert_eq!(derivation.lines().count(), 8);
+	assert!(derivation.starts_with("   ¬(A ⇒ B) ⇔ C\n"));
+	assert!(derivation.ends_with("=  ((¬A ∨ B) ∨ C) ∧ (¬C ∨ (A ∧ ¬B))      [replace_junction_negation at rr]"));
+
+	let mut ast = BoolNode::tree("AB&CD&|");
+	let trace = ast.to_cnf_traced();
+	assert_eq!(trace.to_string(), "   (A ∧ B) ∨ (C ∧ D)\n\
+		=  (A ∨ C) ∧ ((A ∨ D) ∧ ((B ∨ C) ∧ (B ∨ D)))   [distribute at root]");
+	let mut ast = BoolNode::tree("AB|C&");
+	let trace = ast.to_dnf_traced();
+	assert_eq!(trace.steps.iter().map(|step| step.rule).collect::<Vec<_>>(),
+		vec!["distribute"]);
+	assert_eq!(BoolNode::tree("A").to_cnf_traced().steps, vec![]);
+
+	for formula in ["AB&CD||", "ABCDE&||&", "AB&C|D|EF&&", "AB=C>", "AB^C!D>&!",
+		"{ABC}2≤D|", "ABA∃&", "BF&DF&&G!H&&BDFIJ&&&&BDFI|&|KL||&&"].iter() {
+		for conversion in [BoolNode::to_nnf_traced, BoolNode::to_cnf_traced,
+			BoolNode::to_dnf_traced].iter() {
+			let orig = BoolNode::tree(formula);
+			let mut mine = orig.clone();
+			let trace = conversion(&mut mine);
+			let formulas = trace.formulas();
+			assert_eq!(formulas.len(), trace.steps.len() + 1);
+			assert_eq!(formulas.last().unwrap().to_formula(), mine.to_formula());
+			for formula in formulas.iter() {
+				assert_eq!(&orig, formula);
+			}
+		}
+		let mut traced = BoolNode::tree(formula);
+		let mut untraced = traced.clone();
+		traced.to_cnf_traced();
+		untraced.to_cnf();
+		assert_eq!(traced.to_formula(), untraced.to_formula());
+	}
+}
+
+#[test]
+fn bounded_conversions() {
+	// (A ∧ B) ∨ (C ∧ D) ∨ ... has 2^n clauses
+	let pairs = "AB&CD&|EF&|GH&|IJ&|KL&|MN&|OP&|";
+	let unlimited = Budget::default();
+	let mut ast = BoolNode::tree(pairs);
+	let budget = Budget { max_clauses: Some(100), ..unlimited };
+	assert_eq!(ast.to_cnf_bounded(&budget), Err(BudgetExceeded::Clauses(128)));
+	assert_eq!(ast.to_formula(), pairs);
+	let budget = Budget { max_nodes: Some(500), ..unlimited };
+	assert!(matches!(ast.to_cnf_bounded(&budget), Err(BudgetExceeded::Nodes(n)) if n > 500));
+	assert_eq!(ast.to_formula(), pairs);
+	let budget = Budget { max_clauses: Some(256), max_nodes: Some(5000), ..unlimited };
+	assert_eq!(ast.to_cnf_bounded(&budget), Ok(()));
+	assert_eq!(ast.conjuncts().len(), 256);
+	let mut expected = BoolNode::tree(pairs);
+	expected.to_cnf();
+	assert_eq!(ast.to_formula(), expected.to_formula());
+
+	let mut ast = BoolNode::tree(pairs);
+	let budget = Budget { max_clauses: Some(1), ..unlimited };
+	assert_eq!(ast.to_dnf_bounded(&budget), Err(BudgetExceeded::Clauses(8)));
+	assert_eq!(ast.to_dnf_bounded(&unlimited), Ok(()));
+	assert!(ast.disjunctive_normal_form());
+
+	// the NNF is checked before it is built
+	let mut ast = BoolNode::tree("AB=CD=^EF=^GH=^");
+	let budget = Budget { max_nodes: Some(1000), ..unlimited };
+	assert!(matches!(ast.to_cnf_bounded(&budget), Err(BudgetExceeded::Nodes(n)) if n > 1000));
+
+	// expansions are checked before they are built: "at most 20 of 40" has
+	// C(40, 21) sets of operands, and each quantifier doubles the formula
+	let at_most = "{ABCDEFGHIJKLMNOPQRSTUVWXYZA!B!C!D!E!F!G!H!I!J!K!L!M!N!}20≤";
+	let mut ast = BoolNode::tree(at_most);
+	let budget = Budget { max_nodes: Some(1000), ..unlimited };
+	assert!(matches!(ast.to_cnf_bounded(&budget), Err(BudgetExceeded::Nodes(n)) if n > 1000));
+	assert_eq!(ast.to_formula(), at_most);
+	let budget = Budget { max_time: Some(Duration::from_millis(10)), ..unlimited };
+	assert!(matches!(ast.to_cnf_bounded(&budget), Err(BudgetExceeded::Time(_))));
+	let quantified = "AN^BO^|CP^|DQ^|ER^|FS^|GT^|HU^|IV^|JW^|KX^|LY^|MZ^|\
+		A∀B∀C∀D∀E∀F∀G∀H∀I∀J∀K∀L∀M∀";
+	let mut ast = BoolNode::tree(quantified);
+	let budget = Budget { max_nodes: Some(1000), ..unlimited };
+	assert!(matches!(ast.to_dnf_bounded(&budget), Err(BudgetExceeded::Nodes(n)) if n > 1000));
+	assert_eq!(ast.to_formula(), quantified);
+
+	let mut ast = BoolNode::tree(pairs);
+	let budget = Budget { max_time: Some(Duration::from_nanos(1)), ..unlimited };
+	let error = ast.to_cnf_bounded(&budget).unwrap_err();
+	assert!(matches!(error, BudgetExceeded::Time(_)));
+	assert!(error.to_string().starts_with("conversion timed out after"));
+	assert_eq!(BudgetExceeded::Clauses(12).to_string(), "conversion needs at least 12 clauses");
+}
+
+#[test]
+fn nary_junctions() {
+	let mut ast = BoolNode::tree("AB&C&DE|F|&");
+	ast.to_nary();
+	assert!(ast.is_nary());
+	assert_eq!(ast.operands().len(), 4);
+	assert_eq!(ast.operands()[3].operands().len(), 3);
+	assert_eq!(ast.to_formula(), "ABCDEF||&&&");
+	assert_eq!(ast.to_infix(), "A ∧ B ∧ C ∧ (D ∨ E ∨ F)");
+	assert_eq!(ast.to_string().lines().count(), 8);
+	assert_eq!(ast, BoolNode::tree("AB&C&DE|F|&"));
+	assert_eq!(Canonical::new(&ast), Canonical::new(&BoolNode::tree("ABCDEF||&&&")));
+	ast.to_binary();
+	assert!(!ast.is_nary());
+	assert_eq!(ast.to_formula(), "ABCDEF||&&&");
+
+	assert_eq!(BoolNode::nary('&', vec![]).to_formula(), "1");
+	assert_eq!(BoolNode::nary('|', vec![]).to_formula(), "0");
+	assert_eq!(BoolNode::nary('|', vec![BoolNode::variable('A')]).to_formula(), "A");
+	let ast = BoolNode::nary('|', vec![BoolNode::variable('A'), BoolNode::constant(false),
+		BoolNode::variable('B')]);
+	assert!(ast.eval(&0b10u32));
+	assert!(!ast.eval(&0u32));
+	assert_eq!(ast.simplify().to_formula(), "AB|");
+	assert_eq!(ast.substitute('B', &BoolNode::constant(true)).simplify().to_formula(), "1");
+	assert_eq!(ast.conjuncts().len(), 1);
+	assert_eq!(ast.disjuncts().len(), 3);
+
+	let mut ast = BoolNode::tree("ABC&&D|!");
+	ast.to_nary();
+	ast.to_nnf();
+	assert!(ast.negation_normal_form());
+	assert_eq!(ast.to_formula(), "A!B!C!||D!&");
+
+	// the clauses of the n-ary form are flat whatever the nesting was
+	let mut ast = BoolNode::tree("AB|CD&|EF&&");
+	ast.to_cnf_nary();
+	assert!(ast.is_nary() && ast.conjunctive_normal_form());
+	let clauses: Vec<usize> = ast.operands().iter()
+		.map(|clause| clause.operands().len())
+		.collect();
+	assert_eq!(clauses, vec![3, 3, 0, 0]);
+	assert_eq!(ast.to_formula(), "ABC||ABD||EF&&&");
+	let mut ast = BoolNode::tree("AB|CD|&E|");
+	ast.to_nary();
+	ast.to_dnf_nary();
+	assert!(ast.disjunctive_normal_form());
+	assert_eq!(ast.to_formula(), "AC&AD&BC&BD&E||||");
+
+	for formula in ["AB|CD&|", "AB&C|D|EF&&", "ABCDE&||&", "AB^C!D>&!",
+		"AB=CD=^", "{ABC}2≤D|"].iter() {
+		let mut nary = BoolNode::tree(formula);
+		nary.to_nary();
+		let mut traced = BoolNode::tree(formula);
+		traced.to_cnf_traced();
+		nary.to_cnf();
+		assert!(!nary.to_formula().contains('{'));
+		assert_eq!(nary.to_formula(), traced.to_formula());
+		assert_eq!(nary, BoolNode::tree(formula));
+		let mut dnf = BoolNode::tree(formula);
+		let trace = dnf.clone().to_dnf_traced();
+		dnf.to_dnf();
+		assert_eq!(trace.formulas().last().unwrap().to_formula(), dnf.to_formula());
+	}
+}
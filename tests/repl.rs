@@ -0,0 +1,58 @@
+#![cfg(feature = "repl")]
+
+use ready_set_boole::repl;
+use ready_set_boole::utils::{ast::BoolNode, truth};
+
+#[test]
+fn repl_steps_and_undo() {
+	let mut session = repl::Session::new();
+	assert!(session.execute("let f = AB>C&").is_ok());
+	assert!(session.execute("step f material l").is_ok());
+	assert_eq!(session.get("f").unwrap().to_formula(), "A!B|C&");
+	let output = session.execute("step f de-morgan").unwrap();
+	assert!(output.ends_with("(unchanged)"));
+	assert!(session.execute("g = f").is_ok());
+	assert!(session.execute("step g distribute-and").is_ok());
+	assert_eq!(session.get("g").unwrap().to_formula(), "A!C&BC&|");
+	assert_eq!(session.execute("eq f g"), Ok(String::from("equivalent")));
+	assert_eq!(session.execute("eq f AB&"), Ok(String::from("not equivalent")));
+
+	assert!(session.execute("undo f").is_ok());
+	assert_eq!(session.get("f").unwrap().to_formula(), "AB>C&");
+	assert_eq!(session.execute("undo f"),
+		Err(String::from("nothing to undo for 'f'")));
+	assert_eq!(session.execute("list"),
+		Ok(String::from("f = AB>C&\ng = A!C&BC&|")));
+
+	assert!(session.execute("let h = AB=!C>").is_ok());
+	assert!(session.execute("step h nnf all").is_ok());
+	assert!(session.get("h").unwrap().negation_normal_form());
+	assert!(session.execute("let r = AB&C&").is_ok());
+	assert!(session.execute("step r rotate-right-and").is_ok());
+	assert_eq!(session.get("r").unwrap().to_formula(), "ABC&&");
+}
+
+#[test]
+fn repl_show_and_errors() {
+	let mut session = repl::Session::new();
+	let tree = BoolNode::tree("AB|");
+	let output = session.execute("AB|").unwrap();
+	assert!(output.starts_with(&tree.to_string()));
+	assert!(output.ends_with("rpn: AB|\ninfix: A ∨ B"));
+	assert_eq!(session.execute("show AB|"), Ok(output));
+	let table = truth::table::<Vec<u8>>("AB|", None).unwrap();
+	assert_eq!(session.execute("table AB|"), Ok(table.trim_end().to_string()));
+
+	assert_eq!(session.execute("show f"), Err(String::from("'f' is not defined")));
+	assert_eq!(session.execute("let F = A"),
+		Err(String::from("'F' is not a valid name")));
+	assert_eq!(session.execute("A&"),
+		Err(String::from("missing operand for 'Conjunction' operation")));
+	assert!(session.execute("let f = AB&").is_ok());
+	assert_eq!(session.execute("step f bogus"),
+		Err(String::from("'bogus' is not a valid rule")));
+	assert_eq!(session.execute("step f material rl"),
+		Err(String::from("no node at path 'rl'")));
+	assert_eq!(session.execute("frobnicate f A B"),
+		Err(String::from("invalid command 'frobnicate f A B' (try 'help')")));
+}
@@ -0,0 +1,512 @@
+use ready_set_boole::utils::sets::*;
+use ready_set_boole::utils::bitset::*;
+use ready_set_boole::utils::interval::*;
+use ready_set_boole::utils::ast::BoolNode;
+
+#[test]
+fn powerset_basics() {
+	let input: Vec<i32> = vec![];
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+
+	let input: Vec<i32> = vec![0];
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+
+	let input: Vec<i32> = vec![0, 1];
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+
+	let input: Vec<i32> = vec![0, 1, 2];
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+
+	let input: Vec<i32> = vec![0, 1, 2, 3];
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+
+	let input: Vec<i32> = vec![0, 1, 2, 3, 4];
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+
+	let input: Vec<i32> = (0..10).collect();
+	let pow = powerset(&input);
+	assert_eq!(pow.len(), 2usize.pow(input.len() as u32));
+}
+
+#[test]
+#[should_panic(expected = "the powerset of 128 elements does not fit in memory")]
+fn powerset_big_input() {
+	let input: Vec<i32> = (0..128).collect();
+	powerset(&input);
+}
+
+#[test]
+#[should_panic(expected = "the powerset of 60 elements does not fit in memory")]
+fn powerset_too_big_for_vec() {
+	let input: Vec<i32> = (0..60).collect();
+	powerset(&input);
+}
+
+#[test]
+fn eval_set_subject_tests() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 3, 4]];
+	let result = eval_set("AB&", &sets);
+	assert_eq!(result, vec![0]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![3, 4, 5]];
+	let result = eval_set("AB|", &sets);
+	assert_eq!(result, vec![0, 1, 2, 3, 4, 5]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2]];
+	let result = eval_set("A!", &sets);
+	assert_eq!(result, vec![]);
+}
+
+#[test]
+fn eval_set_other_ops() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 3, 4]];
+	let result = eval_set("AB^", &sets);
+	assert_eq!(result, vec![1, 2, 3, 4]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2, 5], vec![0, 3, 4, 5, 6]];
+	let result = eval_set("AB^!", &sets);
+	assert_eq!(result, vec![0, 5]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 3, 4]];
+	let result = eval_set("AB>", &sets);
+	assert_eq!(result, vec![0, 3, 4]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 3, 4]];
+	let result = eval_set("AB>!", &sets);
+	assert_eq!(result, vec![1, 2]);
+
+	// '=' is element-wise: 55 is neither in A nor in B
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 1, 2], vec![55]];
+	let result = eval_set("AB=CC^|", &sets);
+	assert_eq!(result, vec![0, 1, 2, 55]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 1, 2], vec![55]];
+	let result = eval_set("AB=CC^|!", &sets);
+	assert_eq!(result, vec![]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 1, 2]];
+	let result = eval_set("AB=", &sets);
+	assert_eq!(result, vec![0, 1, 2]);
+
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![1, 3], vec![4]];
+	let result = eval_set("AB=", &sets[..2]);
+	assert_eq!(result, vec![1]);
+	let result = eval_set("AB=C|", &sets);
+	assert_eq!(result, vec![1, 4]);
+	assert_eq!(eval_set("AB=", &sets[..2]), eval_set("AB&A!B!&|", &sets[..2]));
+}
+
+#[test]
+fn set_predicates() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![1, 2], vec![5, 6]];
+	assert!(is_subset("B", "A", &sets[..2]));
+	assert!(!is_subset("A", "B", &sets[..2]));
+	assert!(is_subset("AB&", "B", &sets[..2]));
+	assert!(is_equal("AB&", "B", &sets[..2]));
+	assert!(is_equal("AB>", "AB!&!", &sets[..2]));
+	assert!(!is_equal("A", "B", &sets[..2]));
+	assert!(is_disjoint("A", "B", &[sets[0].clone(), sets[2].clone()]));
+	assert!(is_disjoint("AB&", "C", &sets));
+	assert!(is_disjoint("AB!&", "B", &sets[..2]));
+	assert!(!is_disjoint("A", "B", &sets[..2]));
+	let roles = vec![vec!["read", "write"], vec!["read"]];
+	assert!(is_subset("B", "A", &roles));
+}
+
+#[test]
+fn eval_set_generic_elements() {
+	let sets: Vec<Vec<String>> = vec![
+		vec![String::from("pear"), String::from("apple"), String::from("fig")],
+		vec![String::from("fig"), String::from("kiwi")],
+	];
+	assert_eq!(eval_set::<String>("AB&", &sets), vec![String::from("fig")]);
+	assert_eq!(eval_set("AB^", &sets), vec!["apple", "kiwi", "pear"]);
+	assert_eq!(eval_set("A!B&", &sets), vec![String::from("kiwi")]);
+	assert_eq!(eval_set("AB>", &sets), vec!["fig", "kiwi"]);
+
+	let sets: Vec<Vec<(u8, char)>> = vec![vec![(1, 'a'), (2, 'b')], vec![(2, 'b'), (3, 'c')]];
+	assert_eq!(eval_set("AB|", &sets), vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+	assert_eq!(eval_set("AB=", &sets), vec![(2, 'b')]);
+
+	let pow = powerset(&["x", "y"]);
+	assert_eq!(pow, vec![vec![], vec!["x"], vec!["y"], vec!["x", "y"]]);
+	assert_eq!(powerset_i32(&[1, 2, 3]).len(), 8);
+	assert_eq!(eval_set_i32("AB|!", &[vec![1], vec![]]), vec![]);
+}
+
+#[test]
+fn eval_set_explicit_universe() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2], vec![0, 3, 4]];
+	let universe = Universe::finite(vec![5, 4, 3, 2, 1, 0, 5]);
+	assert_eq!(eval_set_in("A!B&", &sets, &universe), vec![3, 4]);
+	assert_eq!(eval_set_in("A!", &sets[..1], &universe), vec![3, 4, 5]);
+	assert_eq!(eval_set_in("AB>", &sets, &universe), vec![0, 3, 4, 5]);
+	assert_eq!(eval_set("AB>", &sets), vec![0, 3, 4]);
+
+	let universe = Universe::range(0..=9);
+	assert!(universe.contains(&9) && !universe.contains(&10));
+	assert_eq!(eval_set_in("AB|!", &sets, &universe), vec![5, 6, 7, 8, 9]);
+	assert_eq!(eval_set_in("AB^", &sets, &universe), vec![1, 2, 3, 4]);
+
+	// only bounded results can be listed in a predicate universe
+	let universe = Universe::predicate(|id: &i32| *id >= 0);
+	assert!(universe.elements().is_none());
+	assert_eq!(eval_set_in("AB!&", &sets, &universe), vec![1, 2]);
+
+	let sets = vec![vec![String::from("admin")], vec![String::from("guest")]];
+	let users = Universe::finite(vec![String::from("admin"), String::from("guest"),
+		String::from("root")]);
+	assert_eq!(eval_set_in("AB|!", &sets, &users), vec![String::from("root")]);
+}
+
+#[test]
+#[should_panic(expected = "set 'B' is not a subset of the universe")]
+fn eval_set_outside_universe() {
+	eval_set_in("AB&", &[vec![1], vec![2, 10]], &Universe::range(0..=9));
+}
+
+#[test]
+#[should_panic(expected = "the result of 'A!' is not finite in a predicate universe")]
+fn eval_set_unbounded_predicate() {
+	eval_set_in("A!", &[vec![1]], &Universe::predicate(|id: &i32| *id > 0));
+}
+
+#[test]
+fn eval_multiset_ops() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 0, 1, 2, 2, 2], vec![0, 2, 2, 3, 3]];
+	assert_eq!(eval_multiset("AB&", &sets), vec![0, 2, 2]);
+	assert_eq!(eval_multiset("AB|", &sets), vec![0, 0, 1, 2, 2, 2, 3, 3]);
+	assert_eq!(eval_multiset("AB+", &sets), vec![0, 0, 0, 1, 2, 2, 2, 2, 2, 3, 3]);
+	assert_eq!(eval_multiset("AB-", &sets), vec![0, 1, 2]);
+	assert_eq!(eval_multiset("BA-", &sets), vec![3, 3]);
+	assert_eq!(eval_multiset("AB^", &sets), vec![0, 1, 2, 3, 3]);
+	assert_eq!(eval_multiset("AB^", &sets), eval_multiset("AB-BA-+", &sets));
+	assert_eq!(eval_multiset("AB|", &sets), eval_multiset("AB&AB^+", &sets));
+	assert_eq!(eval_multiset("AA+A-", &sets[..1]), sets[0]);
+	assert_eq!(eval_multiset("AA-", &sets[..1]), vec![]);
+
+	// unordered input and other element types
+	let sets = vec![vec!["nut", "bolt", "nut"], vec!["nut", "washer"], vec!["bolt"]];
+	assert_eq!(eval_multiset("AB+C-", &sets), vec!["nut", "nut", "nut", "washer"]);
+	assert_eq!(eval_multiset("AB|C&", &sets), vec!["bolt"]);
+	assert_eq!(eval_multiset("A", &sets[..1]), vec!["bolt", "nut", "nut"]);
+
+	// without duplicates '&', '|' and '^' are the set operations
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2, 5], vec![0, 3, 4, 5, 6]];
+	for formula in ["AB&", "AB|", "AB^"] {
+		assert_eq!(eval_multiset(formula, &sets), eval_set(formula, &sets));
+	}
+}
+
+#[test]
+#[should_panic(expected = "'!' is not a valid multiset op or is missing an argument")]
+fn eval_multiset_complement() {
+	eval_multiset("AB!&", &[vec![1], vec![2]]);
+}
+
+#[test]
+#[should_panic(expected = "unused operands in formula string")]
+fn eval_multiset_unused_operands() {
+	eval_multiset("AB", &[vec![1], vec![2]]);
+}
+
+#[test]
+fn eval_set_rewritten_trees() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2, 3], vec![2, 3, 4], vec![3, 5, 6]];
+	for formula in ["AB>C!^", "AB=C|!", "ABC&|A!B>&", "AB^BC^=", "{ABC}1≤"] {
+		let varmap = variable_sets(formula, &sets);
+		let tree = BoolNode::tree(formula);
+		let expected = eval_set_tree(&tree, &varmap);
+		if !formula.contains('{') {
+			assert_eq!(eval_set(formula, &sets), expected, "{}", formula);
+		}
+		for rewrite in [BoolNode::to_nnf, BoolNode::to_cnf, BoolNode::to_dnf,
+			BoolNode::to_cnf_nary, BoolNode::to_dnf_nary] {
+			let mut tree = tree.clone();
+			rewrite(&mut tree);
+			assert_eq!(eval_set_tree(&tree, &varmap), expected, "{} -> {}",
+				formula, tree.to_formula());
+		}
+	}
+	// constants and dropped variables keep the universe of the formula
+	let tree_set = |formula: &str, sets: &[Vec<i32>]| {
+		eval_set_tree(&BoolNode::tree(formula), &variable_sets(formula, sets))
+	};
+	let formula = "AB0&|C!|";
+	let tree = BoolNode::tree(formula).simplify();
+	assert_eq!(tree.to_formula(), "AC!|");
+	assert_eq!(eval_set_tree(&tree, &variable_sets(formula, &sets)), tree_set(formula, &sets));
+	assert_eq!(tree_set(formula, &sets), eval_set("AC!|B!B&|", &sets));
+	assert_eq!(eval_set("AA!|", &sets[..1]), vec![0, 1, 2, 3]);
+	assert_eq!(tree_set("A1&", &sets[..1]), vec![0, 1, 2, 3]);
+	assert_eq!(tree_set("A0|", &sets[..1]), vec![0, 1, 2, 3]);
+	assert_eq!(tree_set("A1!|", &sets[..1]), vec![0, 1, 2, 3]);
+	assert_eq!(tree_set("A0&", &sets[..1]), vec![]);
+	assert_eq!(tree_set("{AB}1#", &sets[..2]), vec![0, 1, 4]);
+	assert_eq!(tree_set("{ABC}2≥", &sets), vec![2, 3]);
+	assert_eq!(tree_set("{ABC}0≤", &sets), vec![]);
+	assert_eq!(tree_set("AB&B∃", &[vec![1], vec![2]]), vec![1]);
+	assert_eq!(tree_set("AB&B∀", &[vec![1], vec![2]]), vec![]);
+	assert_eq!(tree_set("AB|B∀", &[vec![1], vec![2]]), vec![1]);
+}
+
+#[test]
+#[should_panic(expected = "'1' is not a valid op or is missing an argument")]
+fn eval_set_constant() {
+	eval_set("A1&", &[vec![0, 1]]);
+}
+
+#[test]
+#[should_panic(expected = "'{' is not a valid op or is missing an argument")]
+fn eval_set_cardinality() {
+	eval_set("{AB}1#", &[vec![0, 1], vec![1, 2]]);
+}
+
+#[test]
+#[should_panic(expected = "'∃' is not a valid op or is missing an argument")]
+fn eval_set_quantifier() {
+	eval_set("AB&B∃", &[vec![0, 1], vec![1, 2]]);
+}
+
+#[test]
+#[should_panic(expected = "'&' is not a valid op or is missing an argument")]
+fn eval_set_missing_argument() {
+	eval_set("AB&&", &[vec![0, 1], vec![1, 2]]);
+}
+
+#[test]
+#[should_panic(expected = "the stack should be empty at the end")]
+fn eval_set_unused_operand() {
+	eval_set("ABA", &[vec![0, 1], vec![1, 2]]);
+}
+
+#[test]
+#[should_panic(expected = "'0' is not a valid op or is missing an argument")]
+fn eval_set_in_constant() {
+	eval_set_in("A0|", &[vec![0, 1]], &Universe::range(0..=3));
+}
+
+#[test]
+#[should_panic(expected = "no set for variable 'B'")]
+fn eval_set_tree_missing_set() {
+	let varmap = variable_sets("A", &[vec![1, 2]]);
+	eval_set_tree(&BoolNode::tree("AB&"), &varmap);
+}
+
+#[test]
+fn eval_set_bitsets() {
+	let universe: Vec<i32> = (-5..70).collect();
+	let index = Index::new(universe.clone());
+	let sets: Vec<Vec<i32>> = vec![(0..40).collect(), (30..65).step_by(3).collect(),
+		vec![-5, 0, 64, 69]];
+	for formula in ["AB&C|", "A!BC&|", "AB^C^", "AB>C>", "AB=C|", "AB=C=", "AB|C&!", "AC>B&!"].iter() {
+		let expected = eval_set_in(formula, &sets, &Universe::finite(universe.clone()));
+		assert_eq!(index.eval_set::<DenseSet>(formula, &sets), expected, "{}", formula);
+		assert_eq!(index.eval_set::<CompressedSet>(formula, &sets), expected, "{}", formula);
+	}
+	let a: DenseSet = index.bitset(&sets[0]);
+	let b: DenseSet = index.bitset(&sets[1]);
+	assert_eq!(index.elements(&a.difference(&b)), eval_set_in("AB!&", &sets[..2],
+		&Universe::finite(universe.clone())));
+	assert_eq!(a.equivalence(&b).count(), 75 - a.symmetric_difference(&b).count());
+	assert!(a.intersection(&b).is_subset(&b) && !a.is_subset(&b));
+	assert!(a.difference(&b).is_disjoint(&b));
+	assert_eq!(DenseSet::full(75).count(), 75);
+	assert!(DenseSet::full(128).complement().is_empty());
+	assert_eq!(index.position(&-5), Some(0));
+}
+
+#[test]
+fn compressed_bitsets() {
+	let size = 300_000;
+	let evens: Vec<usize> = (0..size).step_by(2).collect();
+	let sparse: Vec<usize> = (0..size).step_by(1000).collect();
+	let mut a = CompressedSet::empty(size);
+	let mut b = CompressedSet::empty(size);
+	let mut dense_a = DenseSet::empty(size);
+	let mut dense_b = DenseSet::empty(size);
+	for position in evens.iter() {
+		a.insert(*position);
+		dense_a.insert(*position);
+	}
+	for position in sparse.iter() {
+		b.insert(*position);
+		dense_b.insert(*position);
+	}
+	// 5 chunks, all of them dense for the even positions
+	assert_eq!((a.chunk_count(), a.bitmap_chunks()), (5, 5));
+	assert_eq!((b.chunk_count(), b.bitmap_chunks()), (5, 0));
+	assert_eq!(a.count(), size / 2);
+	assert!(a.contains(299_998) && !a.contains(299_999) && !a.contains(size));
+
+	let ops: [fn(&CompressedSet, &CompressedSet) -> CompressedSet; 7] = [
+		|a, _| a.complement(), CompressedSet::intersection, CompressedSet::union,
+		CompressedSet::symmetric_difference, CompressedSet::difference,
+		CompressedSet::implication, CompressedSet::equivalence,
+	];
+	let dense_ops: [fn(&DenseSet, &DenseSet) -> DenseSet; 7] = [
+		|a, _| a.complement(), DenseSet::intersection, DenseSet::union,
+		DenseSet::symmetric_difference, DenseSet::difference,
+		DenseSet::implication, DenseSet::equivalence,
+	];
+	for (op, dense_op) in ops.iter().zip(dense_ops.iter()) {
+		for (x, y, dx, dy) in [(&a, &b, &dense_a, &dense_b), (&b, &a, &dense_b, &dense_a)].iter() {
+			assert_eq!(op(x, y).positions(), dense_op(dx, dy).positions());
+		}
+	}
+	assert_eq!(a.intersection(&b).count(), sparse.len());
+	assert_eq!(b.complement().complement(), b);
+	assert_eq!(eval_bitset("AB|!", &[a.clone(), b.clone()]).count(), size / 2);
+	assert_eq!(eval_bitset("AB&", &[a, b.clone()]), b);
+}
+
+#[test]
+fn powerset_iterator() {
+	let set: Vec<i32> = (0..6).collect();
+	let all: Vec<Vec<i32>> = Powerset::new(&set).collect();
+	assert_eq!(all, powerset(&set));
+	assert_eq!(Powerset::new(&set).remaining_len(), Some(64));
+	for (index, subset) in all.iter().enumerate() {
+		assert_eq!(Powerset::new(&set).nth(index).as_ref(), Some(subset));
+	}
+	assert_eq!(Powerset::new(&set).nth(64), None);
+	let mut iter = Powerset::new(&set);
+	assert_eq!(iter.nth(10), Some(vec![1, 3]));
+	assert_eq!(iter.remaining_len(), Some(53));
+	assert_eq!(iter.next(), Some(vec![0, 1, 3]));
+
+	// Gray code order: every subset once, one element changing at a time
+	let gray: Vec<Vec<i32>> = Powerset::gray(&set).collect();
+	assert_eq!(gray.len(), 64);
+	for pair in gray.windows(2) {
+		let (a, b) = (&pair[0], &pair[1]);
+		let changed = set.iter().filter(|e| a.contains(e) != b.contains(e)).count();
+		assert_eq!(changed, 1);
+	}
+	let mut sorted = gray.clone();
+	sorted.sort();
+	let mut expected = all.clone();
+	expected.sort();
+	assert_eq!(sorted, expected);
+	assert_eq!(Powerset::gray(&set).nth(5), Some(vec![0, 1, 2]));
+
+	let pairs: Vec<Vec<i32>> = Powerset::combinations(&set[..4], 2).collect();
+	assert_eq!(pairs, vec![vec![0, 1], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3],
+		vec![2, 3]]);
+	let triples: Vec<Vec<i32>> = Powerset::combinations(&set, 3).collect();
+	assert_eq!(triples.len(), 20);
+	for (index, subset) in triples.iter().enumerate() {
+		assert_eq!(Powerset::combinations(&set, 3).nth(index).as_ref(), Some(subset));
+	}
+	assert_eq!(Powerset::combinations(&set, 0).collect::<Vec<_>>(), vec![vec![]]);
+	assert_eq!(Powerset::combinations(&set, 7).next(), None);
+}
+
+#[test]
+fn powerset_iterator_big_input() {
+	let set: Vec<i32> = (0..200).collect();
+	let mut iter = Powerset::new(&set);
+	assert_eq!(iter.size_hint(), (usize::MAX, None));
+	assert_eq!(iter.remaining_len(), None);
+	assert_eq!(iter.nth(usize::MAX), Some((0..64).collect::<Vec<i32>>()));
+	assert_eq!(iter.next(), Some(vec![64]));
+	assert_eq!(Powerset::combinations(&set, 2).remaining_len(), Some(19900));
+	assert_eq!(Powerset::combinations(&set, 198).nth(19899), Some((2..200).collect()));
+	assert_eq!(Powerset::combinations(&set, 100).nth(1), Some((0..99).chain(100..101).collect()));
+	let mut gray = Powerset::gray(&set);
+	assert_eq!(gray.nth(usize::MAX), Some(vec![63]));
+	assert_eq!(gray.next(), Some(vec![63, 64]));
+}
+
+#[test]
+fn interval_sets() {
+	let (start, end) = (30, 29);
+	let set = IntervalSet::from_ranges(vec![10..=20, 1..=3, 4..=5, 15..=25, start..=end, 40..=40]);
+	assert_eq!(set.ranges(), [1..=5, 10..=25, 40..=40]);
+	assert_eq!(set.len(), 22);
+	assert!(set.contains(&4) && set.contains(&25) && set.contains(&40));
+	assert!(!set.contains(&0) && !set.contains(&26) && !set.contains(&39));
+	let mut points = IntervalSet::from(vec![7, 3, 5, 4, 9]);
+	assert_eq!(points.ranges(), [3..=5, 7..=7, 9..=9]);
+	points.insert(8);
+	points.insert_range(0..=2);
+	assert_eq!(points.ranges(), [0..=5, 7..=9]);
+	assert_eq!(set.union(&points).ranges(), [0..=5, 7..=25, 40..=40]);
+	assert_eq!(set.intersection(&points).ranges(), [1..=5]);
+	assert_eq!(set.difference(&points).ranges(), [10..=25, 40..=40]);
+	assert_eq!(set.symmetric_difference(&points).ranges(), [0..=0, 7..=25, 40..=40]);
+	assert_eq!(set.complement(&(0..=50)).ranges(), [0..=0, 6..=9, 26..=39, 41..=50]);
+	assert_eq!(set.implication(&points, &(0..=50)).ranges(), [0..=9, 26..=39, 41..=50]);
+	assert_eq!(set.equivalence(&set, &(0..=50)).ranges(), [0..=50]);
+	assert!(IntervalSet::from(vec![2, 3]).is_subset(&set));
+	assert!(IntervalSet::<i32>::new().is_subset(&IntervalSet::new()));
+	assert!(IntervalSet::<i32>::new().union(&IntervalSet::new()).is_empty());
+
+	// the bounds of the integer type
+	let full = IntervalSet::from(i64::MIN..=i64::MAX);
+	assert_eq!(full.len(), 1u128 << 64);
+	assert!(full.complement(&(i64::MIN..=i64::MAX)).is_empty());
+	let edges = IntervalSet::from(vec![u8::MIN, u8::MAX]);
+	assert_eq!(edges.complement(&(0..=255)).ranges(), [1..=254]);
+	assert_eq!(edges.union(&IntervalSet::from(1..=254)).ranges(), [0..=255]);
+}
+
+#[test]
+fn eval_interval_set_ops() {
+	let ports = IntervalSet::from(1..=1000);
+	let open = IntervalSet::from_ranges(vec![22..=22, 80..=80, 443..=443, 8000..=8080]);
+	let blocked = IntervalSet::from(vec![23, 80, 8080]);
+	let sets = vec![ports, open, blocked];
+	let universe = 0..=u16::MAX;
+	assert_eq!(eval_interval_set("AB&C!&", &sets, universe.clone()).ranges(),
+		[22..=22, 443..=443]);
+	assert_eq!(eval_interval_set("BC|A!&", &sets, universe.clone()).ranges(), [8000..=8080]);
+	assert_eq!(eval_interval_set("A!", &sets[..1], universe.clone()).ranges(),
+		[0..=0, 1001..=65535]);
+	assert_eq!(eval_interval_set("A!!", &sets[..1], 1..=2000), sets[0]);
+
+	// every operator gives the same elements as eval_set_in
+	let sets: Vec<IntervalSet<i32>> = vec![
+		IntervalSet::from_ranges(vec![0..=4, 9..=12]),
+		IntervalSet::from(vec![2, 3, 10, 14]),
+		IntervalSet::from(5..=10),
+	];
+	let points: Vec<Vec<i32>> = sets.iter().map(|set| set.iter().collect()).collect();
+	let universe = Universe::range(-2..=16);
+	for formula in ["AB&", "AB|", "AB^", "AB>", "AB=", "A!", "AB!&C|", "AB=C>!",
+		"ABC&|A!B>&", "AB^BC^="] {
+		let result = eval_interval_set(formula, &sets[..formula_sets(formula)], -2..=16);
+		assert_eq!(result.iter().collect::<Vec<i32>>(),
+			eval_set_in(formula, &points[..formula_sets(formula)], &universe), "{}", formula);
+	}
+}
+
+fn formula_sets(formula: &str) -> usize {
+	['A', 'B', 'C'].iter().filter(|set| formula.contains(**set)).count()
+}
+
+#[test]
+#[should_panic(expected = "set 'B' is not a subset of the universe")]
+fn eval_interval_set_outside_universe() {
+	let sets = vec![IntervalSet::from(1..=10), IntervalSet::from(vec![5, 20])];
+	eval_interval_set("AB|", &sets, 0..=10);
+}
+
+#[test]
+#[should_panic(expected = "'1' is not a valid op or is missing an argument")]
+fn eval_interval_set_constant() {
+	eval_interval_set("B1&", &[IntervalSet::from(1..=10)], 0..=10);
+}
+
+#[test]
+#[should_panic(expected = "'{' is not a valid op or is missing an argument")]
+fn eval_interval_set_cardinality() {
+	let sets = vec![IntervalSet::from(1..=10), IntervalSet::from(vec![5, 7])];
+	eval_interval_set("{AB}1#", &sets, 0..=10);
+}
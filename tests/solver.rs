@@ -0,0 +1,416 @@
+#![cfg(feature = "solver")]
+
+use std::collections::HashMap;
+use ready_set_boole::utils::{ast::*, truth::sat, sat as solver, maxsat::{MaxSat, Weight},
+	encode::{self, Encoding, PseudoBoolean, Relation}};
+
+#[test]
+fn solver_clauses() {
+	let clauses = solver::clauses(&BoolNode::tree("AB&C|"));
+	let expected: Vec<solver::Clause> = vec![
+		vec![solver::Literal::new('A', true), solver::Literal::new('C', true)],
+		vec![solver::Literal::new('B', true), solver::Literal::new('C', true)],
+	];
+	assert_eq!(clauses, expected);
+	assert_eq!(solver::clauses_to_node(&clauses).to_formula(), "AC|BC|&");
+
+	let clauses = solver::clauses(&BoolNode::tree("AA!|B1&0|&"));
+	assert_eq!(clauses, vec![vec![solver::Literal::new('B', true)]]);
+	assert_eq!(solver::clauses(&BoolNode::tree("A0&")),
+		vec![vec![solver::Literal::new('A', true)], vec![]]);
+}
+
+#[test]
+fn solver_solve() {
+	for formula in ["AB|", "AB&", "AA!&", "AA^", "AB>BC>&CA!>&A&",
+		"AB=C^D&AD>&", "AB|A!B|&AB!|&A!B!|&"].iter() {
+		let ast = BoolNode::tree(formula);
+		let model = solver::solve(&solver::clauses(&ast));
+		assert_eq!(model.is_some(), sat(formula));
+		if let Some(model) = model {
+			assert!(ast.eval(&model));
+		}
+	}
+}
+
+#[test]
+fn solver_unsat_core() {
+	let rules: Vec<BoolNode> = ["AB>", "C", "BC^", "A", "D", "B!D|"].iter()
+		.map(|rule| BoolNode::tree(rule))
+		.collect();
+	assert_eq!(solver::unsat_core(&rules), Some(vec![0, 1, 2, 3]));
+	assert_eq!(solver::unsat_core(&rules[..3]), None);
+
+	let formula = BoolNode::tree("DA&AB>&B!&C&");
+	let core: Vec<String> = solver::conjunct_core(&formula).unwrap()
+		.iter().map(|rule| rule.to_formula()).collect();
+	assert_eq!(core, vec!["A", "AB>", "B!"]);
+	assert!(solver::conjunct_core(&BoolNode::tree("AB&C&")).is_none());
+
+	let core = solver::clause_core(&BoolNode::tree("AB|A!B|&C&B!&")).unwrap();
+	assert_eq!(solver::clauses_to_node(&core).to_formula(), "AB|A!B|B!&&");
+}
+
+#[test]
+fn solver_interpolant() {
+	let a = BoolNode::tree("AB&BC>&");
+	let b = BoolNode::tree("C!D&");
+	let interpolant = solver::interpolant(&a, &b).unwrap();
+	assert_eq!(interpolant.free_variables(), vec!['C']);
+	assert!(!solver::satisfiable(&BoolNode::binary('&', a.clone(),
+		BoolNode::unary('!', interpolant.clone()))));
+	assert!(!solver::satisfiable(&BoolNode::binary('&', interpolant, b)));
+
+	assert!(solver::interpolant(&a, &BoolNode::tree("CD&")).is_none());
+	let interpolant = solver::interpolant(&BoolNode::tree("AA!&"),
+		&BoolNode::tree("B")).unwrap();
+	assert_eq!(interpolant.to_formula(), "0");
+}
+
+#[test]
+fn solver_assumptions() {
+	let lit = solver::Literal::new;
+	let mut session = solver::Solver::new();
+	session.add_formula(&BoolNode::tree("AB>"));
+	session.add_formula(&BoolNode::tree("BC!>"));
+	session.add_formula(&BoolNode::tree("DE|"));
+
+	match session.solve(&[lit('A', true)]) {
+		solver::Outcome::Satisfiable(model) => {
+			assert_eq!(model.get(&'A'), Some(&true));
+			assert_eq!(model.get(&'C'), Some(&false));
+		},
+		outcome => panic!("unexpected {:?}", outcome),
+	}
+	let outcome = session.solve(&[lit('D', false), lit('A', true),
+		lit('E', true), lit('C', true)]);
+	assert_eq!(outcome, solver::Outcome::Unsatisfiable(vec![lit('A', true),
+		lit('C', true)]));
+	let outcome = session.solve(&[lit('C', true), lit('A', false)]);
+	assert!(matches!(outcome, solver::Outcome::Satisfiable(_)));
+
+	session.add_clause(vec![lit('E', false)]);
+	session.add_clause(vec![lit('D', false)]);
+	assert_eq!(session.solve(&[lit('A', false)]),
+		solver::Outcome::Unsatisfiable(vec![]));
+}
+
+#[test]
+fn solver_scopes() {
+	let lit = solver::Literal::new;
+	let mut session = solver::Solver::new();
+	session.add_formula(&BoolNode::tree("AB|"));
+	session.push();
+	session.add_formula(&BoolNode::tree("A!"));
+	assert_eq!(session.scope_depth(), 1);
+	assert_eq!(session.solve(&[lit('B', false)]),
+		solver::Outcome::Unsatisfiable(vec![lit('B', false)]));
+	session.push();
+	session.add_formula(&BoolNode::tree("B!"));
+	assert_eq!(session.solve(&[]), solver::Outcome::Unsatisfiable(vec![]));
+	session.pop();
+	assert!(matches!(session.solve(&[]), solver::Outcome::Satisfiable(_)));
+	session.pop();
+	assert_eq!(session.clauses().len(), 1);
+	match session.solve(&[lit('B', false)]) {
+		solver::Outcome::Satisfiable(model) => {
+			assert_eq!(model.get(&'A'), Some(&true));
+		},
+		outcome => panic!("unexpected {:?}", outcome),
+	}
+}
+
+#[test]
+fn solver_against_solve() {
+	let lit = solver::Literal::new;
+	let mut state = 0x2545_f491_4f6c_dd1du64;
+	let mut random = move |range: u64| {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state % range
+	};
+	for _ in 0..20 {
+		let mut session = solver::Solver::new();
+		let mut scopes: Vec<Vec<solver::Clause>> = vec![vec![]];
+		for _ in 0..100 {
+			match random(10) {
+				0 | 1 => {
+					session.push();
+					scopes.push(vec![]);
+				},
+				2 | 3 if scopes.len() > 1 => {
+					session.pop();
+					scopes.pop();
+				},
+				4..=6 => {
+					let clause: solver::Clause = (0..1 + random(3))
+						.map(|_| lit((b'A' + random(8) as u8) as char, random(2) == 0))
+						.collect();
+					session.add_clause(clause.clone());
+					scopes.last_mut().unwrap().push(clause);
+				},
+				_ => {
+					let assumptions: Vec<solver::Literal> = (0..random(5))
+						.map(|_| lit((b'A' + random(8) as u8) as char, random(2) == 0))
+						.collect();
+					let all_clauses: Vec<solver::Clause> = scopes.concat();
+					let with = |literals: &[solver::Literal]| {
+						let mut clauses = all_clauses.clone();
+						clauses.extend(literals.iter().map(|literal| vec![*literal]));
+						clauses
+					};
+					match session.solve(&assumptions) {
+						solver::Outcome::Satisfiable(model) => {
+							for clause in with(&assumptions) {
+								assert!(clause.iter().any(|literal| {
+									model.get(&literal.variable) == Some(&literal.positive)
+								}), "{:?} is false", clause);
+							}
+						},
+						solver::Outcome::Unsatisfiable(failed) => {
+							assert!(failed.iter()
+								.all(|literal| assumptions.contains(literal)));
+							assert!(solver::solve(&with(&failed)).is_none());
+						},
+					}
+				},
+			}
+			assert_eq!(session.clauses(), scopes.concat().as_slice());
+		}
+	}
+}
+
+#[test]
+#[should_panic(expected = "no scope to pop")]
+fn solver_pop_without_scope() {
+	solver::Solver::new().pop();
+}
+
+#[test]
+fn maxsat_basics() {
+	let mut problem = MaxSat::new();
+	problem.add_formula(&BoolNode::tree("AB>"), Weight::Hard);
+	problem.add_formula(&BoolNode::tree("A"), Weight::Soft(3));
+	problem.add_formula(&BoolNode::tree("B!"), Weight::Soft(2));
+	problem.add_formula(&BoolNode::tree("C"), Weight::Soft(1));
+	problem.add_formula(&BoolNode::tree("C!"), Weight::Soft(1));
+	let solution = problem.solve().unwrap();
+	assert_eq!(solution.cost, 3);
+	assert_eq!(solution.satisfied, 4);
+	assert_eq!(solution.assignment.get(&'A'), Some(&true));
+	assert_eq!(solution.assignment.get(&'B'), Some(&true));
+
+	let mut problem = MaxSat::new();
+	problem.add_conjuncts(&BoolNode::tree("AB>A&B!&"),
+		&[Weight::Hard, Weight::Soft(1), Weight::Soft(5)]);
+	let solution = problem.solve().unwrap();
+	assert_eq!(solution.cost, 1);
+	assert_eq!(solution.assignment.get(&'A'), Some(&false));
+
+	problem.add_clause(vec![solver::Literal::new('B', true)], Weight::Hard);
+	assert_eq!(problem.solve().unwrap().cost, 5);
+	problem.add_formula(&BoolNode::tree("B!"), Weight::Hard);
+	assert!(problem.solve().is_none());
+}
+
+#[test]
+fn maxsat_against_brute_force() {
+	let constraints = [
+		("AB|C>", Weight::Hard),
+		("AD^", Weight::Soft(4)),
+		("BC&", Weight::Soft(3)),
+		("C!D!|", Weight::Soft(2)),
+		("A!B!&", Weight::Soft(5)),
+		("DA=B|", Weight::Soft(1)),
+	];
+	let mut problem = MaxSat::new();
+	for (formula, weight) in constraints.iter() {
+		problem.add_formula(&BoolNode::tree(formula), *weight);
+	}
+	let solution = problem.solve().unwrap();
+
+	let mut best = u64::MAX;
+	for values in 0..16u32 {
+		let mut cost = 0;
+		let mut valid = true;
+		for (formula, weight) in constraints.iter() {
+			let satisfied = BoolNode::tree(formula).eval(&values);
+			match (weight, satisfied) {
+				(Weight::Hard, false) => valid = false,
+				(Weight::Soft(w), false) => cost += w,
+				_ => (),
+			}
+		}
+		if valid { best = best.min(cost) }
+	}
+	assert_eq!(solution.cost, best);
+	assert!(BoolNode::tree("AB|C>").eval(&solution.assignment));
+}
+
+#[test]
+fn cardinality_encodings() {
+	let encodings = [Encoding::SequentialCounter, Encoding::Totalizer,
+		Encoding::SortingNetwork];
+	let symbols = ['≤', '≥', '#'];
+	let letters = ['A', 'B', 'C', 'D', 'E'];
+	for n in 1..=letters.len() {
+		for bound in 0..=n + 1 {
+			for symbol in symbols.iter() {
+				let operands: String = letters[..n].iter().collect();
+				let formula = format!("{{{}}}{}{}", operands, bound, symbol);
+				let ast = BoolNode::tree(&formula);
+				for encoding in encodings.iter() {
+					let clauses = encode::clauses(&ast, *encoding);
+					for values in 0..(1u32 << n) {
+						let mut fixed = clauses.clone();
+						for (shift, letter) in letters[..n].iter().enumerate() {
+							let positive = (values >> shift) & 1 != 0;
+							fixed.push(vec![solver::Literal::new(*letter, positive)]);
+						}
+						assert_eq!(solver::solve(&fixed).is_some(), ast.eval(&values),
+							"{} with {:?} on {:#b}", formula, encoding, values);
+					}
+				}
+			}
+		}
+	}
+}
+
+#[test]
+fn cardinality_sat_path() {
+	let ast = BoolNode::tree("{ABCDEF}3≤{ABC}2≥&{DEF}1≥&");
+	for encoding in [Encoding::SequentialCounter, Encoding::Totalizer,
+		Encoding::SortingNetwork].iter() {
+		let model = solver::solve(&encode::clauses(&ast, *encoding)).unwrap();
+		assert!(ast.eval(&model));
+		let mut unsat = BoolNode::binary('&', ast.clone(), BoolNode::tree("{ABC}2#!"));
+		assert!(!encode::satisfiable(&unsat, *encoding));
+		unsat = BoolNode::binary('&', ast.clone(), BoolNode::tree("A"));
+		let cnf = encode::to_cnf(&unsat, *encoding);
+		assert!(cnf.conjunctive_normal_form());
+		assert!(solver::satisfiable(&cnf));
+	}
+}
+
+#[test]
+fn pseudo_boolean_constraints() {
+	let terms: Vec<(u64, BoolNode)> = vec![(3, BoolNode::tree("A")),
+		(2, BoolNode::tree("B")), (2, BoolNode::tree("C!")), (5, BoolNode::tree("DE&"))];
+	for relation in [Relation::AtMost, Relation::AtLeast, Relation::Equal].iter() {
+		for bound in 0..14 {
+			let constraint = PseudoBoolean::new(terms.clone(), *relation, bound);
+			let node = constraint.to_node();
+			let mut fresh = encode::Fresh::for_formula(&node);
+			let clauses = constraint.encode(&mut fresh);
+			for values in 0..32u32 {
+				let sum: u64 = terms.iter()
+					.filter(|(_, x)| x.eval(&values))
+					.map(|(weight, _)| weight)
+					.sum();
+				let expected = match relation {
+					Relation::AtMost => sum <= bound,
+					Relation::AtLeast => sum >= bound,
+					Relation::Equal => sum == bound,
+				};
+				assert_eq!(node.eval(&values), expected);
+				let mut fixed = clauses.clone();
+				for letter in "ABCDE".chars() {
+					fixed.push(vec![solver::Literal::new(letter,
+						values.value(letter).unwrap())]);
+				}
+				assert_eq!(solver::solve(&fixed).is_some(), expected);
+			}
+		}
+	}
+}
+
+#[test]
+fn tseitin_encoding() {
+	for formula in ["AB&CD&|EF&|", "AB=C>", "AB^C!D>&!", "A1&0|B=", "{ABC}2≤D^",
+		"AB∃C|", "AA!&"].iter() {
+		let ast = BoolNode::tree(formula);
+		let variables = ast.free_variables();
+		let clauses = encode::tseitin(&ast, &mut encode::Fresh::for_formula(&ast));
+		assert_eq!(solver::solve(&clauses).is_some(), solver::satisfiable(&ast));
+		for values in 0..(1u32 << variables.len()) {
+			let mut fixed = clauses.clone();
+			let mut varmap: HashMap<char, bool> = HashMap::new();
+			for (shift, variable) in variables.iter().enumerate() {
+				let value = (values >> shift) & 1 != 0;
+				varmap.insert(*variable, value);
+				fixed.push(vec![solver::Literal::new(*variable, value)]);
+			}
+			assert_eq!(solver::solve(&fixed).is_some(), ast.eval(&varmap), "{}", formula);
+		}
+	}
+}
+
+#[test]
+fn tseitin_encoding_nary() {
+	for formula in ["AB&CD&|EF&|", "ABC&&D!EF||&", "AB|C|D&E&", "AB^C&D!&",
+		"{ABC}2≤DE||"].iter() {
+		let mut ast = BoolNode::tree(formula);
+		ast.to_nary();
+		assert!(ast.is_nary(), "{}", formula);
+		let variables = ast.free_variables();
+		let clauses = encode::tseitin(&ast, &mut encode::Fresh::for_formula(&ast));
+		assert_eq!(solver::solve(&clauses).is_some(), solver::satisfiable(&ast));
+		for values in 0..(1u32 << variables.len()) {
+			let mut fixed = clauses.clone();
+			let mut varmap: HashMap<char, bool> = HashMap::new();
+			for (shift, variable) in variables.iter().enumerate() {
+				let value = (values >> shift) & 1 != 0;
+				varmap.insert(*variable, value);
+				fixed.push(vec![solver::Literal::new(*variable, value)]);
+			}
+			assert_eq!(solver::solve(&fixed).is_some(), ast.eval(&varmap), "{}", formula);
+		}
+	}
+}
+
+#[test]
+fn bounded_cnf_fallback() {
+	let unlimited = Budget::default();
+	let ast = BoolNode::tree("AB&CD&|EF&|GH&|IJ&|KL&|MN&|OP&|");
+	let budget = Budget { max_clauses: Some(100), ..unlimited };
+	let cnf = match encode::bounded_cnf(&ast, &budget) {
+		Ok(encode::BoundedCnf::Equisatisfiable(cnf)) => cnf,
+		result => panic!("unexpected {:?}", result),
+	};
+	assert!(cnf.conjunctive_normal_form());
+	assert!(cnf.conjuncts().len() <= 100);
+	let model = solver::solve(&solver::cnf_to_clauses(&cnf)).unwrap();
+	assert!(ast.eval(&model));
+
+	let small = BoolNode::tree("AB&C|");
+	let mut expected = small.clone();
+	expected.to_cnf();
+	let result = encode::bounded_cnf(&small, &budget).unwrap();
+	assert!(matches!(result, encode::BoundedCnf::Equivalent(_)));
+	assert_eq!(result.formula().to_formula(), expected.to_formula());
+	assert!(result.formula().conjunctive_normal_form());
+
+	let budget = Budget { max_clauses: Some(10), ..unlimited };
+	assert!(matches!(encode::bounded_cnf(&ast, &budget), Err(BudgetExceeded::Clauses(_))));
+
+	// the encoding expands the cardinality constraints too
+	let ast = BoolNode::tree("{ABCDEFGHIJKLMNOPQRSTUVWXYZA!B!C!D!E!F!G!H!I!J!K!L!M!N!}20≤");
+	let budget = Budget { max_nodes: Some(1000), ..unlimited };
+	assert!(matches!(encode::bounded_cnf(&ast, &budget), Err(BudgetExceeded::Nodes(_))));
+}
+
+#[test]
+fn equality_of_many_variables() {
+	let variables: Vec<BoolNode> = AUXILIARY_VARIABLES.take(70).map(BoolNode::variable).collect();
+	let all = BoolNode::conjunction(variables.clone());
+	let contradiction = BoolNode::conjunction(vec![all.clone(),
+		BoolNode::unary('!', variables[0].clone())]);
+	assert_ne!(all, contradiction);
+	let mut reversed = variables.clone();
+	reversed.reverse();
+	let none = BoolNode::unary('!', BoolNode::disjunction(variables.clone()));
+	let negated = reversed.into_iter().map(|variable| BoolNode::unary('!', variable)).collect();
+	assert_eq!(none, BoolNode::conjunction(negated));
+	assert_ne!(all, BoolNode::disjunction(variables));
+}
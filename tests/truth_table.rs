@@ -0,0 +1,42 @@
+use ready_set_boole::utils::truth::{self, sat};
+
+#[test]
+fn truth_table_subject_tests() {
+	let expected: &str = "\
+		| A | B | C | = |\n\
+		|---|---|---|---|\n\
+		| 0 | 0 | 0 | 0 |\n\
+		| 0 | 0 | 1 | 1 |\n\
+		| 0 | 1 | 0 | 0 |\n\
+		| 0 | 1 | 1 | 1 |\n\
+		| 1 | 0 | 0 | 0 |\n\
+		| 1 | 0 | 1 | 1 |\n\
+		| 1 | 1 | 0 | 1 |\n\
+		| 1 | 1 | 1 | 1 |\n\
+	";
+	if let Some(truth) = truth::table::<std::io::Stdout>("AB&C|", None) {
+		assert_eq!(truth, expected);
+	} else {
+		panic!("no output string for truth::table function");
+	}
+}
+
+#[test]
+#[should_panic(expected = "formula string is empty")]
+fn truth_table_empty_string() {
+    truth::table::<std::io::Stdout>("", None);
+}
+
+#[test]
+#[should_panic(expected = "'1' is not a valid op")]
+fn truth_table_boolean_value() {
+    truth::table::<std::io::Stdout>("100&Z!", None);
+}
+
+#[test]
+fn sat_subject_tests() {
+	assert_eq!(sat("AB|"), true);
+	assert_eq!(sat("AB&"), true);
+	assert_eq!(sat("AA!&"), false);
+	assert_eq!(sat("AA^"), false);
+}
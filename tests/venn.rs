@@ -0,0 +1,94 @@
+use ready_set_boole::utils::venn::*;
+use ready_set_boole::utils::ast::BoolNode;
+use ready_set_boole::utils::sets::{eval_set, eval_set_tree, variable_sets};
+
+#[test]
+fn venn_regions() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1, 2, 5], vec![1, 3, 5], vec![2, 3, 4, 5]];
+	let venn = Venn::new("AB&C!|", &sets);
+	assert_eq!(venn.variables(), ['A', 'B', 'C']);
+	let regions = venn.regions();
+	assert_eq!(regions.len(), 8);
+	let formulas: Vec<&str> = regions.iter().map(|region| region.formula.as_str()).collect();
+	assert_eq!(formulas, ["A!B!&C!&", "AB!&C!&", "A!B&C!&", "AB&C!&",
+		"A!B!&C&", "AB!&C&", "A!B&C&", "AB&C&"]);
+	let elements: Vec<Vec<i32>> = regions.iter().map(|region| region.elements.clone()).collect();
+	assert_eq!(elements, [vec![], vec![0], vec![], vec![1], vec![4], vec![2], vec![3], vec![5]]);
+	let selected: Vec<usize> = regions.iter()
+		.filter(|region| region.selected)
+		.map(|region| region.mask)
+		.collect();
+	assert_eq!(selected, [0, 1, 2, 3, 7]);
+	assert_eq!(venn.selected_elements(), eval_set("AB&C!|", &sets));
+	for (index, region) in regions.iter().enumerate() {
+		assert_eq!(region.mask, index);
+		assert_eq!(region.elements, eval_set(&region.formula, &sets));
+	}
+
+	let venn = Venn::new("A", &[vec!["x", "y"]]);
+	assert_eq!(venn.regions()[0], Region {
+		mask: 0,
+		formula: "A!".to_string(),
+		elements: vec![],
+		selected: false,
+	});
+	assert_eq!(venn.regions()[1].elements, ["x", "y"]);
+	for formula in ["AB^CD|&", "{ABCD}2#", "AB>C=D!|"] {
+		let sets = vec![vec![0, 1, 2, 3], vec![2, 3, 4], vec![3, 5], vec![0, 5, 6]];
+		assert_eq!(Venn::new(formula, &sets).selected_elements(),
+			eval_set_tree(&BoolNode::tree(formula), &variable_sets(formula, &sets)));
+	}
+}
+
+#[test]
+fn venn_ascii() {
+	for count in 1..=4 {
+		let formula: String = ('A'..='Z').take(count).collect::<String>()
+			+ &"&".repeat(count - 1);
+		let sets: Vec<Vec<usize>> = (0..count).map(|index| vec![index]).collect();
+		let diagram = Venn::new(&formula, &sets).ascii();
+		let lines: Vec<&str> = diagram.lines().collect();
+		assert_eq!(lines.len(), 30);
+		assert!(lines.iter().all(|line| line.chars().count() == 74));
+		assert_eq!(lines[0], lines[29]);
+		for variable in ('A'..='Z').take(count) {
+			assert_eq!(diagram.matches(variable).count(), 1);
+		}
+		assert!(diagram.contains('#'));
+		let filled = diagram.matches('#').count();
+		let negated = Venn::new(&format!("{}!", formula), &sets).ascii();
+		assert_eq!(negated.matches('#').count() + filled, 72 * 28
+			- diagram.matches('.').count() - count);
+	}
+	let empty = Venn::new("AB!&B&", &[vec![1], vec![2]]).ascii();
+	assert!(!empty.contains('#'));
+	let full = Venn::new("AA!|", &[vec![1]]).ascii();
+	assert_eq!(full.matches('#').count(), 72 * 28 - full.matches('.').count() - 1);
+}
+
+#[test]
+fn venn_svg() {
+	let sets: Vec<Vec<i32>> = vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 4]];
+	let diagram = Venn::new("AB&CD|&", &sets).svg();
+	assert!(diagram.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+	assert!(diagram.ends_with("</svg>\n"));
+	assert_eq!(diagram.matches("<clipPath").count(), 4);
+	// ABCD!, ABC!D and ABCD
+	assert_eq!(diagram.matches("<mask").count(), 3);
+	assert_eq!(diagram.matches("mask=\"url(#region-").count(), 3);
+	assert!(diagram.contains("<mask id=\"region-15\">"));
+	assert_eq!(diagram.matches("fill=\"none\" stroke=\"black\"").count(), 4);
+	for variable in ['A', 'B', 'C', 'D'] {
+		assert!(diagram.contains(&format!(">{}</text>", variable)));
+	}
+	let outside = Venn::new("A!", &[vec![1]]).svg();
+	assert!(outside.contains("<mask id=\"region-0\">"));
+	assert!(!outside.contains("<g clip-path"));
+}
+
+#[test]
+#[should_panic(expected = "cannot draw a Venn diagram of 5 sets (1 to 4)")]
+fn venn_too_many_sets() {
+	let sets: Vec<Vec<i32>> = (0..5).map(|index| vec![index]).collect();
+	Venn::new("ABCDE&&&&", &sets).ascii();
+}